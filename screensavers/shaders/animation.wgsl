@@ -0,0 +1,49 @@
+// @name Animation
+// @description Animated GIF, APNG or WebP, looping
+// @author HyprFresh
+// @param file: string = "" -- Animated GIF, APNG or WebP file (a still image works too)
+// @param scale: string = "fit" -- fit (letterboxed), fill (cropped), stretch, or integer (whole multiples, for pixel art)
+// @param background: vec3<f32> = [0.0, 0.0, 0.0] -- Colour of the letterbox bars and behind transparent pixels
+// @param pixelated: bool = false -- Sharp square pixels instead of smooth scaling
+// @param speed: f32 = 1.0 in 0.0..10.0 -- Playback speed multiplier
+// @param memory: f32 = 256.0 in 16.0..4096.0 -- MiB of decoded frames to keep; longer animations are decoded as they play
+// @image frame -- Filled in by the animation itself
+//
+// Frames are decoded and timed in Rust (src/screensavers/animation.rs),
+// which swaps each one into `frame` when it's due. `animation` is (scale
+// mode, loaded, 0, 0); the modes are numbered as in `Scale` there.
+
+@group(0) @binding(2) var<storage, read> animation: vec4<u32>;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(frame));
+    let fit = u.resolution / size;
+    var scale = vec2<f32>(min(fit.x, fit.y));
+    switch animation.x {
+        case 1u: {
+            scale = vec2<f32>(max(fit.x, fit.y));
+        }
+        case 2u: {
+            scale = fit;
+        }
+        case 3u: {
+            // Frames too big for the screen were already shrunk to fit
+            scale = vec2<f32>(max(floor(min(fit.x, fit.y)), 1.0));
+        }
+        default: {}
+    }
+
+    // Pixels into the frame from its top left corner
+    let centred = (uv - 0.5) * u.resolution / scale;
+    var texel = vec2<f32>(centred.x, -centred.y) + size * 0.5;
+    if (params.pixelated != 0u) {
+        texel = floor(texel) + 0.5;
+    }
+    let inside = all(texel >= vec2<f32>(0.0)) && all(texel <= size);
+    // The sampler repeats: keep the opposite edge from bleeding in
+    let st = clamp(texel, vec2<f32>(0.5), size - 0.5) / size;
+    let color = textureSampleLevel(frame, image_sampler, st, 0.0);
+    let shown = select(params.background, mix(params.background, color.rgb, color.a), inside && animation.y != 0u);
+    return vec4<f32>(shown, 1.0);
+}
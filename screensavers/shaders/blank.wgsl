@@ -1,4 +1,6 @@
-// Blank screensaver -- pure black (DPMS-like, minimal power)
+// @name Blank
+// @description Black screen (DPMS-like, minimal power)
+// @author HyprFresh
 
 @fragment
 fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
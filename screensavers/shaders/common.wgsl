@@ -1,8 +1,16 @@
 // Common uniforms shared by all screensavers
 struct Uniforms {
+    // Animation seconds. Built-ins with a `speed` option already scale it;
+    // custom shaders get plain seconds.
     time: f32,
-    _pad: f32,
+    // Overlay opacity (0.0 - 1.0). Already applied by the renderer; don't
+    // multiply the output by it again.
+    opacity: f32,
     resolution: vec2<f32>,
+    // Frames rendered since the screensaver started
+    frame: u32,
+    // Local date: year, month (0-11), day (1-31), seconds since midnight
+    date: vec4<f32>,
 }
 
 @group(0) @binding(0) var<uniform> u: Uniforms;
@@ -0,0 +1,30 @@
+// @name Fireflies
+// @description Fireflies drifting and blinking in the dark
+// @author HyprFresh
+// @param count: i32 = 48 in 1..256 -- Number of fireflies
+// @param speed: f32 = 1.0 in 0.0..10.0 -- Flight speed
+// @param color: vec3<f32> = [1.0, 0.8, 0.3] -- Glow colour
+//
+// The flight is simulated in Rust (src/screensavers/fireflies.rs); each
+// entry of `flies` is (x, y, brightness, size) in uv space.
+
+@group(0) @binding(2) var<storage, read> flies: array<vec4<f32>>;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let aspect = u.resolution.x / u.resolution.y;
+
+    var glow = 0.0;
+    for (var i = 0u; i < arrayLength(&flies); i++) {
+        let fly = flies[i];
+        let d = (uv - fly.xy) * vec2<f32>(aspect, 1.0);
+        let r2 = fly.w * fly.w;
+        // Bright core with a soft halo
+        let s = r2 / (dot(d, d) + r2);
+        glow += fly.z * (0.8 * s * s + 0.1 * s);
+    }
+
+    // Faint night-sky gradient underneath
+    let sky = vec3<f32>(0.01, 0.015, 0.03) * uv.y;
+    return vec4<f32>(min(sky + params.color * glow, vec3<f32>(1.0)), 1.0);
+}
@@ -0,0 +1,56 @@
+// Procedural noise, in [0, 1] unless noted
+
+#import hyprfresh/hash
+
+// Value noise: smoothly interpolated random values on the integer grid
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let w = f * f * (3.0 - 2.0 * f);
+    let a = hash21(i);
+    let b = hash21(i + vec2<f32>(1.0, 0.0));
+    let c = hash21(i + vec2<f32>(0.0, 1.0));
+    let d = hash21(i + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, w.x), mix(c, d, w.x), w.y);
+}
+
+fn simplex_permute(x: vec3<f32>) -> vec3<f32> {
+    return ((x * 34.0 + 1.0) * x) % vec3<f32>(289.0);
+}
+
+// 2D simplex noise (after Ashima Arts / Stefan Gustavson), in [-1, 1]
+fn simplex_noise(v: vec2<f32>) -> f32 {
+    let C = vec4<f32>(0.211324865405187, 0.366025403784439, -0.577350269189626, 0.024390243902439);
+    var i = floor(v + dot(v, C.yy));
+    let x0 = v - i + dot(i, C.xx);
+    var i1 = vec2<f32>(0.0, 1.0);
+    if x0.x > x0.y {
+        i1 = vec2<f32>(1.0, 0.0);
+    }
+    let x12 = x0.xyxy + C.xxzz - vec4<f32>(i1, 0.0, 0.0);
+    i = i % vec2<f32>(289.0);
+    let p = simplex_permute(simplex_permute(i.y + vec3<f32>(0.0, i1.y, 1.0)) + i.x + vec3<f32>(0.0, i1.x, 1.0));
+    var m = max(0.5 - vec3<f32>(dot(x0, x0), dot(x12.xy, x12.xy), dot(x12.zw, x12.zw)), vec3<f32>(0.0));
+    m = m * m;
+    m = m * m;
+    let x = 2.0 * fract(p * C.www) - 1.0;
+    let h = abs(x) - 0.5;
+    let ox = floor(x + 0.5);
+    let a0 = x - ox;
+    m = m * (1.79284291400159 - 0.85373472095314 * (a0 * a0 + h * h));
+    let g = vec3<f32>(a0.x * x0.x + h.x * x0.y, a0.yz * x12.xz + h.yz * x12.yw);
+    return 130.0 * dot(m, g);
+}
+
+// Fractal Brownian motion: `octaves` layers of value noise
+fn fbm(p: vec2<f32>, octaves: i32) -> f32 {
+    var sum = 0.0;
+    var amp = 0.5;
+    var q = p;
+    for (var i = 0; i < octaves; i++) {
+        sum += amp * value_noise(q);
+        q = q * 2.0 + vec2<f32>(17.0, 31.0);
+        amp *= 0.5;
+    }
+    return sum;
+}
@@ -0,0 +1,11 @@
+// Colour palettes
+
+// Cosine palette (Inigo Quilez): a + b * cos(2π(c·t + d))
+fn palette(t: f32, a: vec3<f32>, b: vec3<f32>, c: vec3<f32>, d: vec3<f32>) -> vec3<f32> {
+    return a + b * cos(6.28318530718 * (c * t + d));
+}
+
+// Smooth rainbow cycling through the hues as t goes from 0 to 1
+fn rainbow(t: f32) -> vec3<f32> {
+    return palette(t, vec3<f32>(0.5), vec3<f32>(0.5), vec3<f32>(1.0), vec3<f32>(0.0, 0.33, 0.67));
+}
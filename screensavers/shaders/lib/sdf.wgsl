@@ -0,0 +1,38 @@
+// Signed distance functions: negative inside, positive outside
+
+fn sd_circle(p: vec2<f32>, r: f32) -> f32 {
+    return length(p) - r;
+}
+
+// Box centred on the origin with half-extents `b`
+fn sd_box(p: vec2<f32>, b: vec2<f32>) -> f32 {
+    let d = abs(p) - b;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+fn sd_rounded_box(p: vec2<f32>, b: vec2<f32>, r: f32) -> f32 {
+    return sd_box(p, b - vec2<f32>(r)) - r;
+}
+
+// Distance to the segment from `a` to `b`
+fn sd_segment(p: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = clamp(dot(pa, ba) / dot(ba, ba), 0.0, 1.0);
+    return length(pa - ba * h);
+}
+
+fn sd_sphere(p: vec3<f32>, r: f32) -> f32 {
+    return length(p) - r;
+}
+
+// Union of two shapes with the seam rounded over distance `k`
+fn op_smooth_union(d1: f32, d2: f32, k: f32) -> f32 {
+    let h = clamp(0.5 + 0.5 * (d2 - d1) / k, 0.0, 1.0);
+    return mix(d2, d1, h) - k * h * (1.0 - h);
+}
+
+// Anti-aliased fill: 1 inside the shape, 0 outside
+fn sd_fill(d: f32, px: f32) -> f32 {
+    return clamp(0.5 - d / px, 0.0, 1.0);
+}
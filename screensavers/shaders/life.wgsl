@@ -0,0 +1,65 @@
+// @name Life
+// @description Conway's Game of Life with fading trails
+// @author HyprFresh
+// @param cell_size: i32 = 8 in 2..64 -- Cell size in pixels
+// @param color: vec3<f32> = [0.3, 1.0, 0.5] -- Colour of living cells
+// @param trail: f32 = 0.92 in 0.0..0.99 -- How slowly dead cells fade
+// @pass life
+//
+// One generation per frame, so `fps` sets the pace. The `life` pass keeps
+// the board: every pixel of a cell holds r = alive, g = trail and a = 1
+// once seeded, so a freshly cleared texture reseeds itself.
+
+#import hyprfresh/hash
+
+// Chance per cell and frame of a spontaneous birth, so the board never
+// settles for good
+const SPARK: f32 = 0.00005;
+
+fn cell_size() -> i32 {
+    return max(params.cell_size, 1);
+}
+
+// State of a cell, wrapping around the edges
+fn cell(c: vec2<i32>) -> vec4<f32> {
+    let dims = vec2<i32>(textureDimensions(life));
+    let cells = (dims + cell_size() - 1) / cell_size();
+    let wrapped = (c + cells) % cells;
+    return textureLoad(life, min(wrapped * cell_size() + cell_size() / 2, dims - 1), 0);
+}
+
+@fragment
+fn fs_life(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let c = vec2<i32>(pos.xy) / cell_size();
+    let noise = hash31(vec3<f32>(vec2<f32>(c), f32(u.frame % 4096u)));
+    let prev = cell(c);
+    if prev.a == 0.0 {
+        let alive = f32(noise < 0.2);
+        return vec4<f32>(alive, alive, 0.0, 1.0);
+    }
+
+    var neighbours = 0;
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            if dx != 0 || dy != 0 {
+                neighbours += i32(cell(c + vec2<i32>(dx, dy)).r > 0.5);
+            }
+        }
+    }
+    let was_alive = prev.r > 0.5;
+    let alive = neighbours == 3 || (was_alive && neighbours == 2) || noise < SPARK;
+    let trail = select(prev.g * params.trail, 1.0, alive);
+    return vec4<f32>(f32(alive), trail, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>, @location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let state = textureLoad(life, vec2<i32>(pos.xy), 0);
+
+    // Rounded cells with a dark gap between them
+    let local = fract(pos.xy / f32(cell_size())) - 0.5;
+    let shape = 1.0 - smoothstep(0.3, 0.45, length(local));
+
+    let glow = state.r + 0.4 * state.g * state.g;
+    return vec4<f32>(params.color * glow * shape, 1.0);
+}
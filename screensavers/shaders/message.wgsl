@@ -0,0 +1,18 @@
+// @name Message
+// @description An away message, bouncing around the screen or scrolling past
+// @author HyprFresh
+// @param message: string = "Away from keyboard" -- Text to show, one or more lines; `hyprfresh ctl message` replaces it until cleared
+// @param animation: string = "bounce" -- bounce, marquee or still
+// @param speed: f32 = 80.0 in 0.0..2000.0 -- Pixels per second
+// @param size: f32 = 96.0 in 8.0..1024.0 -- Line height in pixels
+// @param font: string = "" -- TrueType/OpenType font file (default: the built-in DejaVu Sans Mono)
+// @param color: vec3<f32> = [1.0, 1.0, 1.0] -- Text colour
+// @param background: vec3<f32> = [0.0, 0.0, 0.0] -- Background colour
+//
+// The text is laid out and moved in Rust (src/screensavers/message.rs) and
+// drawn over this background by the renderer's text pipeline.
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(params.background, 1.0);
+}
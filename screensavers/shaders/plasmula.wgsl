@@ -1,4 +1,7 @@
-// Plasmula — Dark plasma waves
+// @name Plasmula
+// @description Dracula-themed plasma waves
+// @author HyprFresh
+// @param speed: f32 = 1.0 in 0.0..10.0 -- Wave speed
 //
 // Layered sine-wave plasma effect with a dark palette.
 // Primary: #6000FF (electric purple), #00FF6C (neon green)
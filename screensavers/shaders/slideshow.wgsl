@@ -0,0 +1,52 @@
+// @name Slideshow
+// @description Images from a folder, crossfading with a slow pan and zoom
+// @author HyprFresh
+// @param directory: string = "~/Pictures" -- Folder of PNG/JPEG images (searched recursively)
+// @param interval: f32 = 10.0 in 1.0..3600.0 -- Seconds per image
+// @param fade: f32 = 1.5 in 0.0..30.0 -- Crossfade length in seconds
+// @param shuffle: bool = false -- Random order instead of sorted by path
+// @param fill: bool = false -- Crop to fill the screen instead of fitting inside it
+// @param pan_zoom: bool = true -- Slowly pan and zoom each image
+// @param lockstep: bool = false -- Show the same image on every monitor at once
+// @image slide_a -- Filled in by the slideshow itself
+// @image slide_b -- Filled in by the slideshow itself
+//
+// The images are picked and decoded in Rust (src/screensavers/slideshow.rs)
+// and alternate between `slide_a` and `slide_b`. Each entry of `slides` is
+// (seconds shown, seed, crossfade weight, loaded) for the image of the
+// same index.
+
+@group(0) @binding(2) var<storage, read> slides: array<vec4<f32>>;
+
+// How far pan and zoom magnify by the end of an image; matches
+// PAN_ZOOM_MARGIN in slideshow.rs
+const ZOOM: f32 = 1.15;
+
+fn slide(tex: texture_2d<f32>, state: vec4<f32>, uv: vec2<f32>) -> vec4<f32> {
+    let size = vec2<f32>(textureDimensions(tex));
+    let fit = u.resolution / size;
+    let scale = select(min(fit.x, fit.y), max(fit.x, fit.y), params.fill != 0u);
+    // Centred position in the image, which spans -0.5..0.5
+    var p = (uv - 0.5) * u.resolution / (size * scale);
+
+    if (params.pan_zoom != 0u) {
+        let t = clamp(state.x / (params.interval + params.fade), 0.0, 1.0);
+        let zoom = mix(1.0, ZOOM, t);
+        // Drift in a direction of its own, within the margin the zoom opens up
+        let angle = fract(sin(state.y * 91.7) * 43758.5453) * 6.2831853;
+        p = p / zoom + vec2<f32>(cos(angle), sin(angle)) * 0.5 * (1.0 - 1.0 / zoom);
+    }
+
+    let st = vec2<f32>(p.x + 0.5, 0.5 - p.y);
+    let inside = all(st >= vec2<f32>(0.0)) && all(st <= vec2<f32>(1.0));
+    let color = textureSampleLevel(tex, image_sampler, st, 0.0);
+    return select(vec4<f32>(0.0, 0.0, 0.0, 1.0), color, inside && state.w != 0.0);
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let a = slide(slide_a, slides[0], uv);
+    let b = slide(slide_b, slides[1], uv);
+    let color = a * slides[0].z + b * slides[1].z;
+    return vec4<f32>(color.rgb, 1.0);
+}
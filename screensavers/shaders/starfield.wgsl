@@ -1,14 +1,12 @@
-// Classic starfield fly-through effect
+// @name Starfield
+// @description Classic starfield fly-through
+// @author HyprFresh
+// @param speed: f32 = 1.0 in 0.0..10.0 -- Fly-through speed
+// @param stars: i32 = 200 in 1..2000 -- Number of stars
 //
 // Stars appear to fly toward the viewer from a central vanishing point.
 
-fn hash2(p: vec2<f32>) -> vec2<f32> {
-    let q = vec2<f32>(
-        dot(p, vec2<f32>(127.1, 311.7)),
-        dot(p, vec2<f32>(269.5, 183.3))
-    );
-    return fract(sin(q) * 43758.5453);
-}
+#import hyprfresh/hash
 
 @fragment
 fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
@@ -26,17 +24,18 @@ fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
         let speed = 0.1 + depth * 0.3;
 
         // Tile space for star placement
-        let scale = 10.0 + f32(layer) * 8.0;
+        // More stars = more, smaller cells (200 is the original density)
+        let scale = (10.0 + f32(layer) * 8.0) * sqrt(max(f32(params.stars), 1.0) / 200.0);
         let st = p * scale;
         let cell = floor(st);
         let cell_uv = fract(st) - 0.5;
 
         // Star position within cell (random offset)
-        let star_pos = hash2(cell + f32(layer) * 100.0) - 0.5;
+        let star_pos = hash22(cell + f32(layer) * 100.0) - 0.5;
         let d = length(cell_uv - star_pos * 0.8);
 
         // Star size varies with time (simulates z-motion)
-        let z = fract(t * speed + hash2(cell).x);
+        let z = fract(t * speed + hash22(cell).x);
         let size = mix(0.001, 0.04, z * z);
 
         // Brightness: brighter as stars get "closer"
@@ -46,7 +45,7 @@ fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
         let star_color = mix(
             vec3<f32>(0.8, 0.9, 1.0),
             vec3<f32>(1.0, 0.95, 0.8),
-            hash2(cell + 50.0).x
+            hash22(cell + 50.0).x
         );
 
         color += star_color * brightness * depth;
@@ -0,0 +1,35 @@
+// Text drawn over a screensaver (the clock): one quad per glyph, in pixels
+// from the top left of the text, sampling a coverage atlas
+
+struct TextUniforms {
+    // RGB and opacity
+    color: vec4<f32>,
+    resolution: vec2<f32>,
+    // Top left corner of the text on screen, in pixels
+    offset: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> text: TextUniforms;
+@group(0) @binding(1) var atlas: texture_2d<f32>;
+@group(0) @binding(2) var atlas_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>, @location(1) uv: vec2<f32>) -> VertexOutput {
+    let p = (position + text.offset) / text.resolution;
+    var out: VertexOutput;
+    out.position = vec4<f32>(p.x * 2.0 - 1.0, 1.0 - p.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let alpha = textureSample(atlas, atlas_sampler, uv).r * text.color.a;
+    // Premultiplied, like the surface
+    return vec4<f32>(text.color.rgb * alpha, alpha);
+}
@@ -0,0 +1,156 @@
+//! Clock overlay
+//!
+//! What the clock shows and where: the local time formatted with the C
+//! library's strftime, placed per `[screensaver.clock]` and drifting slowly
+//! around that spot so it doesn't burn into OLED panels. The renderer draws
+//! it over the screensaver with its text pipeline (see `text`).
+
+use crate::config::{ClockConfig, ClockPosition};
+use crate::text::Align;
+use std::ffi::CString;
+
+/// strftime conversions (after `%`, flags, a width and `E`/`O`)
+const CONVERSIONS: &str = "aAbBcCdDeFgGhHIjklmMnpPrRsStTuUVwWxXyYzZ%+";
+
+/// Longest formatted clock, in bytes
+const MAX_LEN: usize = 512;
+
+/// Seconds for one sweep of the drift, across and up and down; different
+/// so the path doesn't repeat for a long time
+const DRIFT_PERIODS: [f64; 2] = [613.0, 887.0];
+
+/// Check a format the way strftime will read it
+pub fn check_format(format: &str) -> Result<(), String> {
+    if format.contains('\0') {
+        return Err("can't contain NUL characters".to_string());
+    }
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        while chars.next_if(|c| "_-0^#".contains(*c)).is_some() {}
+        while chars.next_if(char::is_ascii_digit).is_some() {}
+        chars.next_if(|c| *c == 'E' || *c == 'O');
+        match chars.next() {
+            Some(c) if CONVERSIONS.contains(c) => {}
+            Some(c) => return Err(format!("unknown conversion '%{}'", c)),
+            None => return Err("ends with an unfinished '%'".to_string()),
+        }
+    }
+    Ok(())
+}
+
+/// Break a time (seconds since the epoch) down in the local time zone
+pub fn local_tm(secs: i64) -> Option<libc::tm> {
+    let secs = secs as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we pass it
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return None;
+    }
+    Some(tm)
+}
+
+/// Format a time (seconds since the epoch) in the local time zone.
+/// Empty if the format can't be used.
+pub fn format_time(format: &str, secs: i64) -> String {
+    let Ok(format) = CString::new(format) else {
+        return String::new();
+    };
+    let Some(tm) = local_tm(secs) else {
+        return String::new();
+    };
+    let mut buf = vec![0u8; MAX_LEN];
+    // SAFETY: strftime writes at most `buf.len()` bytes into buf
+    let len = unsafe { libc::strftime(buf.as_mut_ptr().cast(), buf.len(), format.as_ptr(), &tm) };
+    buf.truncate(len);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// How lines line up for a position, so the clock hugs its edge
+pub fn align(position: ClockPosition) -> Align {
+    match position {
+        ClockPosition::TopLeft | ClockPosition::Left | ClockPosition::BottomLeft => Align::Left,
+        ClockPosition::Top | ClockPosition::Center | ClockPosition::Bottom => Align::Center,
+        ClockPosition::TopRight | ClockPosition::Right | ClockPosition::BottomRight => Align::Right,
+    }
+}
+
+/// Top left corner of the clock in pixels, for text of size `text` on a
+/// `screen`, `secs` seconds since the epoch. Whole pixels, so the text stays
+/// sharp while it drifts.
+pub fn position(config: &ClockConfig, text: [f32; 2], screen: [f32; 2], secs: f64) -> [f32; 2] {
+    let (column, row) = match config.position {
+        ClockPosition::TopLeft => (0, 0),
+        ClockPosition::Top => (1, 0),
+        ClockPosition::TopRight => (2, 0),
+        ClockPosition::Left => (0, 1),
+        ClockPosition::Center => (1, 1),
+        ClockPosition::Right => (2, 1),
+        ClockPosition::BottomLeft => (0, 2),
+        ClockPosition::Bottom => (1, 2),
+        ClockPosition::BottomRight => (2, 2),
+    };
+    // Keep clear of the edges by half a line, plus room to drift
+    let margin = config.size * 0.5 + config.drift;
+    let place = |axis: usize, slot: i32| {
+        let free = screen[axis] - text[axis];
+        let spot = match slot {
+            0 => margin,
+            1 => free / 2.0,
+            _ => free - margin,
+        };
+        let phase = (secs / DRIFT_PERIODS[axis]).fract() * std::f64::consts::TAU;
+        let drift = config.drift * phase.sin() as f32;
+        (spot + drift).clamp(0.0, free.max(0.0)).round()
+    };
+    [place(0, column), place(1, row)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_formats() {
+        for good in ["%H:%M", "%-d %B %Y\n%H:%M:%S", "%Ex %OH", "100%%", ""] {
+            assert!(check_format(good).is_ok(), "{}", good);
+        }
+        assert!(check_format("%H:%Q").unwrap_err().contains("'%Q'"));
+        assert!(check_format("%H:%").is_err());
+        assert!(check_format("a\0b").is_err());
+    }
+
+    #[test]
+    fn formats_local_time() {
+        assert_eq!(format_time("%Y", 86400 * 365 + 86400 * 180), "1971");
+        assert_eq!(format_time("%S\nok", 61), "01\nok");
+        assert_eq!(format_time("", 0), "");
+    }
+
+    #[test]
+    fn places_the_clock_and_drifts_around_it() {
+        let mut config = ClockConfig {
+            drift: 0.0,
+            size: 100.0,
+            ..ClockConfig::default()
+        };
+        let screen = [1920.0, 1080.0];
+        let text = [300.0, 100.0];
+        assert_eq!(position(&config, text, screen, 0.0), [1570.0, 930.0]);
+        config.position = ClockPosition::Center;
+        assert_eq!(position(&config, text, screen, 0.0), [810.0, 490.0]);
+        config.position = ClockPosition::TopLeft;
+        assert_eq!(position(&config, text, screen, 0.0), [50.0, 50.0]);
+
+        // Never off screen, however far it drifts
+        config.drift = 20.0;
+        for secs in (0..2000).map(|s| s as f64 * 0.7) {
+            let [x, y] = position(&config, text, screen, secs);
+            assert!((50.0..=90.0).contains(&x) && (50.0..=90.0).contains(&y));
+        }
+        assert_ne!(position(&config, text, screen, 0.0), position(&config, text, screen, 100.0));
+        assert_eq!(position(&config, [4000.0, 100.0], screen, 50.0)[0], 0.0);
+    }
+}
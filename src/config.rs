@@ -1,9 +1,13 @@
+use crate::clock;
+use crate::screensavers;
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
 
 /// Top-level configuration for HyprFresh
 #[derive(Debug, Default, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
     pub general: GeneralConfig,
@@ -13,10 +17,15 @@ pub struct Config {
 
     #[serde(default)]
     pub screensaver: ScreensaverConfig,
+
+    /// Named screensaver setups, usable anywhere a screensaver name is
+    #[serde(default)]
+    pub presets: HashMap<String, PresetConfig>,
 }
 
 /// General daemon settings
 #[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct GeneralConfig {
     /// Per-monitor idle timeout in seconds (default: 300 = 5 minutes)
     #[serde(default = "default_idle_timeout")]
@@ -37,7 +46,11 @@ pub struct GeneralConfig {
 }
 
 /// Per-monitor overrides
+///
+/// Screensaver settings are merged on top of `[screensaver]`: anything set
+/// here wins, anything left out is inherited. `options` are merged key by key.
 #[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct MonitorConfig {
     /// Override idle timeout for this monitor
     pub idle_timeout: Option<u64>,
@@ -45,6 +58,19 @@ pub struct MonitorConfig {
     /// Override screensaver for this monitor
     pub screensaver: Option<String>,
 
+    /// Override target FPS for this monitor
+    pub fps: Option<u32>,
+
+    /// Override overlay opacity for this monitor
+    pub opacity: Option<f32>,
+
+    /// Screensaver options for this monitor, layered over `[screensaver.options]`
+    #[serde(default)]
+    pub options: HashMap<String, toml::Value>,
+
+    /// Show or hide the clock on this monitor (set up in `[screensaver.clock]`)
+    pub clock: Option<bool>,
+
     /// Disable screensaver on this monitor
     #[serde(default)]
     pub disabled: bool,
@@ -52,13 +78,15 @@ pub struct MonitorConfig {
 
 /// Screensaver rendering settings
 #[allow(dead_code)]
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
 pub struct ScreensaverConfig {
     /// Which screensaver to use (default: "matrix")
     #[serde(default = "default_screensaver")]
     pub name: String,
 
-    /// Target FPS for screensaver animation (default: 30)
+    /// Target FPS for screensaver animation (default: 30).
+    /// 0 renders a single frame, for static savers like `blank`.
     #[serde(default = "default_fps")]
     pub fps: u32,
 
@@ -69,6 +97,110 @@ pub struct ScreensaverConfig {
     /// Screensaver-specific options (passed to the screensaver module)
     #[serde(default)]
     pub options: HashMap<String, toml::Value>,
+
+    /// Clock drawn over the screensaver
+    #[serde(default)]
+    pub clock: ClockConfig,
+}
+
+/// Clock and date overlay (`[screensaver.clock]`)
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ClockConfig {
+    /// Draw the clock (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// strftime-style format; `\n` starts a new line (default: "%H:%M")
+    #[serde(default = "default_clock_format")]
+    pub format: String,
+
+    /// TrueType/OpenType font file (default: the built-in DejaVu Sans Mono)
+    pub font: Option<String>,
+
+    /// Text height in pixels (default: 96)
+    #[serde(default = "default_clock_size")]
+    pub size: f32,
+
+    /// RGB text colour (default: white)
+    #[serde(default = "default_clock_color")]
+    pub color: [f32; 3],
+
+    /// Text opacity (0.0 - 1.0, default: 0.8)
+    #[serde(default = "default_clock_opacity")]
+    pub opacity: f32,
+
+    /// Where on the screen (default: "bottom-right")
+    #[serde(default)]
+    pub position: ClockPosition,
+
+    /// How far the clock wanders from its spot, in pixels, to spread wear
+    /// on OLED panels; 0 keeps it still (default: 32)
+    #[serde(default = "default_clock_drift")]
+    pub drift: f32,
+}
+
+/// Screen position of the clock
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClockPosition {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    #[default]
+    BottomRight,
+}
+
+/// A named screensaver with its own settings (`[presets.NAME]`)
+///
+/// Settings set here win over `[screensaver]`; per-monitor overrides still
+/// win over the preset.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PresetConfig {
+    /// Screensaver this preset runs (built-in or custom, not another preset)
+    pub screensaver: String,
+
+    pub fps: Option<u32>,
+
+    pub opacity: Option<f32>,
+
+    #[serde(default)]
+    pub options: HashMap<String, toml::Value>,
+
+    /// Show or hide the clock (set up in `[screensaver.clock]`)
+    pub clock: Option<bool>,
+}
+
+impl PresetConfig {
+    /// One-line summary for `--list`
+    pub fn describe(&self) -> String {
+        let mut parts = vec![self.screensaver.clone()];
+        if let Some(fps) = self.fps {
+            parts.push(format!("{} fps", fps));
+        }
+        if let Some(opacity) = self.opacity {
+            parts.push(format!("opacity {}", opacity));
+        }
+        match self.clock {
+            Some(true) => parts.push("clock".to_string()),
+            Some(false) => parts.push("no clock".to_string()),
+            None => {}
+        }
+        let mut options: Vec<String> = self
+            .options
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        options.sort();
+        parts.extend(options);
+        parts.join(", ")
+    }
 }
 
 // Default value functions
@@ -90,6 +222,21 @@ fn default_opacity() -> f32 {
 fn default_true() -> bool {
     true
 }
+fn default_clock_format() -> String {
+    "%H:%M".to_string()
+}
+fn default_clock_size() -> f32 {
+    96.0
+}
+fn default_clock_color() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
+fn default_clock_opacity() -> f32 {
+    0.8
+}
+fn default_clock_drift() -> f32 {
+    32.0
+}
 
 impl GeneralConfig {
     /// Effective session idle timeout: explicit value or falls back to idle_timeout
@@ -116,19 +263,490 @@ impl Default for ScreensaverConfig {
             fps: default_fps(),
             opacity: default_opacity(),
             options: HashMap::new(),
+            clock: ClockConfig::default(),
+        }
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: default_clock_format(),
+            font: None,
+            size: default_clock_size(),
+            color: default_clock_color(),
+            opacity: default_clock_opacity(),
+            position: ClockPosition::default(),
+            drift: default_clock_drift(),
+        }
+    }
+}
+
+/// Upper bound for `fps`; beyond this the limiter can't do anything useful
+const MAX_FPS: u32 = 1000;
+
+/// Largest clock text, in pixels
+const MAX_CLOCK_SIZE: f32 = 1024.0;
+
+/// The ext-idle-notify timeout is a u32 in milliseconds
+const MAX_SESSION_IDLE_TIMEOUT: u64 = u32::MAX as u64 / 1000;
+
+/// How serious a config problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The daemon refuses to use this config
+    Error,
+    /// Usable, but probably not what was meant
+    Warning,
+}
+
+/// A problem found while checking a config file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Key the problem is about, e.g. `["screensaver", "opacity"]`
+    /// (empty for syntax errors)
+    pub key: Vec<String>,
+    pub message: String,
+    /// 1-based line and column in the source, if known
+    pub location: Option<(usize, usize)>,
+}
+
+impl Diagnostic {
+    fn error(key: &[&str], message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            key: key.iter().map(|k| k.to_string()).collect(),
+            message,
+            location: None,
+        }
+    }
+
+    fn warning(key: &[&str], message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(key, message)
+        }
+    }
+
+    /// Format as `path:line:column: severity: key: message`
+    pub fn render(&self, path: &str) -> String {
+        match self.location {
+            Some((line, col)) => format!("{}:{}:{}: {}", path, line, col, self),
+            None => format!("{}: {}", path, self),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        if self.key.is_empty() {
+            write!(f, "{}: {}", severity, self.message)
+        } else {
+            write!(f, "{}: {}: {}", severity, self.key.join("."), self.message)
         }
     }
 }
 
 impl Config {
-    /// Load configuration from a TOML file
+    /// Load configuration from a TOML file, rejecting anything `check_source`
+    /// reports as an error
     pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
-        Ok(config)
+        let (config, diagnostics) = Config::check_source(&content);
+        match config {
+            Some(config) if !diagnostics.iter().any(|d| d.severity == Severity::Error) => {
+                Ok(config)
+            }
+            _ => Err(render_errors(path, &diagnostics).into()),
+        }
+    }
+
+    /// Parse and validate config source.
+    ///
+    /// Unknown keys and syntax errors come from the parser; values that parse
+    /// but can't work at runtime (zero intervals, out-of-range opacity,
+    /// unknown screensavers) are checked afterwards. Returns the config if it
+    /// parsed at all, plus everything found, located in `source`.
+    pub fn check_source(source: &str) -> (Option<Self>, Vec<Diagnostic>) {
+        let config: Config = match toml::from_str(source) {
+            Ok(config) => config,
+            Err(e) => {
+                let diagnostic = Diagnostic {
+                    location: e.span().map(|span| line_col(source, span.start)),
+                    ..Diagnostic::error(&[], e.message().to_string())
+                };
+                return (None, vec![diagnostic]);
+            }
+        };
+
+        let mut diagnostics = config.problems();
+        locate_all(source, &mut diagnostics);
+        (Some(config), diagnostics)
+    }
+
+    /// Warn about `[monitors.NAME]` sections that match no connected output
+    pub fn check_outputs(&self, source: &str, outputs: &[String]) -> Vec<Diagnostic> {
+        let mut names: Vec<&String> = self.monitors.keys().collect();
+        names.sort();
+        let mut diagnostics: Vec<Diagnostic> = names
+            .into_iter()
+            .filter(|name| !outputs.contains(name))
+            .map(|name| {
+                Diagnostic::warning(
+                    &["monitors", name],
+                    format!("no connected output named '{}' (connected: {})", name, outputs.join(", ")),
+                )
+            })
+            .collect();
+        locate_all(source, &mut diagnostics);
+        diagnostics
+    }
+
+    /// Values that parse fine but can't work at runtime
+    fn problems(&self) -> Vec<Diagnostic> {
+        let mut problems = Vec::new();
+        let general = &self.general;
+
+        if general.idle_timeout == 0 {
+            problems.push(Diagnostic::error(
+                &["general", "idle_timeout"],
+                "must be greater than 0".to_string(),
+            ));
+        }
+        if general.poll_interval == 0 {
+            problems.push(Diagnostic::error(
+                &["general", "poll_interval"],
+                "must be greater than 0".to_string(),
+            ));
+        }
+        match general.session_idle_timeout {
+            Some(0) => problems.push(Diagnostic::error(
+                &["general", "session_idle_timeout"],
+                "must be greater than 0".to_string(),
+            )),
+            Some(secs) if secs > MAX_SESSION_IDLE_TIMEOUT => problems.push(Diagnostic::error(
+                &["general", "session_idle_timeout"],
+                format!("must be at most {} seconds", MAX_SESSION_IDLE_TIMEOUT),
+            )),
+            _ => {}
+        }
+
+        let saver = &self.screensaver;
+        if let Err(e) = self.resolve_name(&saver.name) {
+            problems.push(Diagnostic::error(&["screensaver", "name"], e));
+        }
+        check_rendering(&mut problems, &["screensaver"], Some(saver.fps), Some(saver.opacity));
+        let global_saver = self.resolve_screensaver("", &saver.name).name;
+        check_options(&mut problems, &["screensaver"], &global_saver, &saver.options);
+        check_clock(&mut problems, &saver.clock);
+
+        let mut presets: Vec<_> = self.presets.iter().collect();
+        presets.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, preset) in presets {
+            if let Err(e) = screensavers::resolve(&preset.screensaver) {
+                let e = if self.presets.contains_key(&preset.screensaver) {
+                    "presets can't refer to other presets".to_string()
+                } else {
+                    e
+                };
+                problems.push(Diagnostic::error(&["presets", name, "screensaver"], e));
+            }
+            if screensavers::is_valid(name) {
+                problems.push(Diagnostic::warning(
+                    &["presets", name],
+                    format!("shadows the screensaver '{}'", name),
+                ));
+            }
+            check_rendering(&mut problems, &["presets", name], preset.fps, preset.opacity);
+            check_options(&mut problems, &["presets", name], &preset.screensaver, &preset.options);
+        }
+
+        let mut monitors: Vec<_> = self.monitors.iter().collect();
+        monitors.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, mon) in monitors {
+            if mon.idle_timeout == Some(0) {
+                problems.push(Diagnostic::error(
+                    &["monitors", name, "idle_timeout"],
+                    "must be greater than 0".to_string(),
+                ));
+            }
+            if let Some(ref saver) = mon.screensaver
+                && let Err(e) = self.resolve_name(saver)
+            {
+                problems.push(Diagnostic::error(&["monitors", name, "screensaver"], e));
+            }
+            check_rendering(&mut problems, &["monitors", name], mon.fps, mon.opacity);
+            let saver = self.screensaver_for(name).name;
+            check_options(&mut problems, &["monitors", name], &saver, &mon.options);
+        }
+
+        problems
+    }
+
+    /// Effective screensaver settings for a monitor: `[screensaver]`, then
+    /// the preset it names (if any), then the monitor's overrides on top
+    pub fn screensaver_for(&self, monitor: &str) -> ScreensaverConfig {
+        self.resolve_screensaver(monitor, self.configured_screensaver(monitor))
+    }
+
+    /// Like `screensaver_for`, but run `name` (a screensaver or preset)
+    /// instead of the configured one
+    pub fn screensaver_named(&self, monitor: &str, name: &str) -> ScreensaverConfig {
+        self.resolve_screensaver(monitor, name)
+    }
+
+    /// Screensaver or preset name configured for a monitor, before
+    /// resolving presets
+    pub fn configured_screensaver(&self, monitor: &str) -> &str {
+        self.monitors
+            .get(monitor)
+            .and_then(|m| m.screensaver.as_deref())
+            .unwrap_or(&self.screensaver.name)
+    }
+
+    /// Check that `name` is a preset or a usable screensaver
+    pub fn resolve_name(&self, name: &str) -> Result<(), String> {
+        if self.presets.contains_key(name) {
+            return Ok(());
+        }
+        screensavers::resolve(name).map_err(|e| {
+            if self.presets.is_empty() {
+                return e;
+            }
+            let mut presets: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+            presets.sort();
+            format!("{}; presets: {}", e, presets.join(", "))
+        })
+    }
+
+    fn resolve_screensaver(&self, monitor: &str, name: &str) -> ScreensaverConfig {
+        let mut saver = self.screensaver.clone();
+        saver.name = name.to_string();
+
+        if let Some(preset) = self.presets.get(name) {
+            saver.name = preset.screensaver.clone();
+            apply_overrides(&mut saver, preset.fps, preset.opacity, &preset.options);
+            if let Some(clock) = preset.clock {
+                saver.clock.enabled = clock;
+            }
+        }
+        if let Some(mon) = self.monitors.get(monitor) {
+            apply_overrides(&mut saver, mon.fps, mon.opacity, &mon.options);
+            if let Some(clock) = mon.clock {
+                saver.clock.enabled = clock;
+            }
+        }
+        saver
     }
 }
 
+/// Range checks for the fps/opacity pair found in several tables
+fn check_rendering(
+    problems: &mut Vec<Diagnostic>,
+    table: &[&str],
+    fps: Option<u32>,
+    opacity: Option<f32>,
+) {
+    let key = |field: &'static str| [table, &[field]].concat();
+    if let Some(fps) = fps
+        && fps > MAX_FPS
+    {
+        problems.push(Diagnostic::error(
+            &key("fps"),
+            format!("must be at most {} (got {})", MAX_FPS, fps),
+        ));
+    }
+    if let Some(opacity) = opacity
+        && !(0.0..=1.0).contains(&opacity)
+    {
+        problems.push(Diagnostic::error(
+            &key("opacity"),
+            format!("must be between 0.0 and 1.0 (got {})", opacity),
+        ));
+    }
+}
+
+/// Check an `options` table against the parameters and images its
+/// screensaver declares.
+/// Unknown options are only warned about: `[screensaver.options]` may hold
+/// options for savers that individual monitors switch to.
+fn check_options(
+    problems: &mut Vec<Diagnostic>,
+    table: &[&str],
+    saver: &str,
+    options: &HashMap<String, toml::Value>,
+) {
+    // Unknown savers are reported on their own
+    let Some(header) = screensavers::header(saver) else {
+        return;
+    };
+    let mut keys: Vec<&String> = options.keys().collect();
+    keys.sort();
+    for key in keys {
+        let path = [table, &["options", key]].concat();
+        let checked = match (header.param(key), header.image(key)) {
+            (Some(decl), _) => Some(decl.check(&options[key]).map(|_| ())),
+            (_, Some(decl)) => Some(decl.check(&options[key]).map(|_| ())),
+            _ => None,
+        };
+        match checked {
+            Some(Ok(())) => {}
+            Some(Err(e)) => problems.push(Diagnostic::error(&path, e)),
+            None => {
+                let known: Vec<&str> = header
+                    .params
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .chain(header.images.iter().map(|i| i.name.as_str()))
+                    .collect();
+                problems.push(Diagnostic::warning(
+                    &path,
+                    format!(
+                        "'{}' has no option '{}' (options: {})",
+                        saver,
+                        key,
+                        if known.is_empty() { "none".to_string() } else { known.join(", ") }
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Values in `[screensaver.clock]` that can't be drawn. Checked even when
+/// it's disabled, since monitors and presets can turn it on.
+fn check_clock(problems: &mut Vec<Diagnostic>, clock: &ClockConfig) {
+    let key = |field: &'static str| ["screensaver", "clock", field];
+    if let Err(e) = clock::check_format(&clock.format) {
+        problems.push(Diagnostic::error(&key("format"), e));
+    }
+    if let Some(ref font) = clock.font
+        && let Err(e) = crate::text::load_font(font)
+    {
+        problems.push(Diagnostic::error(&key("font"), e));
+    }
+    if !(1.0..=MAX_CLOCK_SIZE).contains(&clock.size) {
+        problems.push(Diagnostic::error(
+            &key("size"),
+            format!("must be between 1 and {} (got {})", MAX_CLOCK_SIZE, clock.size),
+        ));
+    }
+    if clock.color.iter().any(|c| !(0.0..=1.0).contains(c)) {
+        problems.push(Diagnostic::error(
+            &key("color"),
+            format!("components must be between 0.0 and 1.0 (got {:?})", clock.color),
+        ));
+    }
+    if !(0.0..=1.0).contains(&clock.opacity) {
+        problems.push(Diagnostic::error(
+            &key("opacity"),
+            format!("must be between 0.0 and 1.0 (got {})", clock.opacity),
+        ));
+    }
+    if !clock.drift.is_finite() || clock.drift < 0.0 {
+        problems.push(Diagnostic::error(
+            &key("drift"),
+            format!("must be 0 or more (got {})", clock.drift),
+        ));
+    }
+}
+
+fn apply_overrides(
+    saver: &mut ScreensaverConfig,
+    fps: Option<u32>,
+    opacity: Option<f32>,
+    options: &HashMap<String, toml::Value>,
+) {
+    if let Some(fps) = fps {
+        saver.fps = fps;
+    }
+    if let Some(opacity) = opacity {
+        saver.opacity = opacity;
+    }
+    saver
+        .options
+        .extend(options.iter().map(|(k, v)| (k.clone(), v.clone())));
+}
+
+/// One line per error, for logs and reload rejections
+pub fn render_errors(path: &str, diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(|d| d.render(path))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn locate_all(source: &str, diagnostics: &mut [Diagnostic]) {
+    for diagnostic in diagnostics {
+        if diagnostic.location.is_none() {
+            diagnostic.location = locate_key(source, &diagnostic.key);
+        }
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let col = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, col)
+}
+
+/// Find where `key` is set in `source`.
+///
+/// Only understands `[table]` headers and `key = value` lines, which is all
+/// this config uses. Keys left at their default (or set in an inline table)
+/// fall back to the closest enclosing table that is found.
+fn locate_key(source: &str, key: &[String]) -> Option<(usize, usize)> {
+    let mut table: Vec<String> = Vec::new();
+    let mut best: Option<(usize, (usize, usize))> = None;
+
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let col = line.len() - trimmed.len() + 1;
+
+        let path = if let Some(header) = trimmed.strip_prefix('[') {
+            let header = header.trim_start_matches('[');
+            let Some(end) = header.find(']') else {
+                continue;
+            };
+            table = split_key(&header[..end]);
+            table.clone()
+        } else if trimmed.starts_with('#') {
+            continue;
+        } else if let Some((lhs, _)) = trimmed.split_once('=') {
+            let mut path = table.clone();
+            path.extend(split_key(lhs));
+            path
+        } else {
+            continue;
+        };
+
+        if key.starts_with(&path) && best.is_none_or(|(len, _)| path.len() > len) {
+            best = Some((path.len(), (i + 1, col)));
+            if path.len() == key.len() {
+                break;
+            }
+        }
+    }
+
+    best.filter(|(len, _)| *len > 0).map(|(_, loc)| loc)
+}
+
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\'').to_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,4 +805,269 @@ mod tests {
         assert!(config.general.session_idle);
         assert_eq!(config.general.effective_session_idle_timeout(), 120);
     }
+
+    fn errors(source: &str) -> Vec<(String, Option<(usize, usize)>)> {
+        let (_, diagnostics) = Config::check_source(source);
+        diagnostics
+            .into_iter()
+            .filter(|d| d.severity == Severity::Error)
+            .map(|d| (d.key.join("."), d.location))
+            .collect()
+    }
+
+    #[test]
+    fn check_accepts_example_config() {
+        let source = include_str!("../config/hyprfresh.toml");
+        let (config, diagnostics) = Config::check_source(source);
+        assert!(config.is_some());
+        assert_eq!(diagnostics, Vec::new());
+    }
+
+    #[test]
+    fn check_rejects_unknown_keys_with_location() {
+        let (config, diagnostics) = Config::check_source("[general]\nidle_timout = 60\n");
+        assert!(config.is_none());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].location, Some((2, 1)));
+        assert!(diagnostics[0].message.contains("unknown field `idle_timout`"));
+
+        let (_, diagnostics) = Config::check_source("[monitors.DP-1]\nscreen_saver = \"matrix\"\n");
+        assert_eq!(diagnostics[0].location, Some((2, 1)));
+    }
+
+    #[test]
+    fn check_rejects_unusable_values() {
+        let source = r#"
+[general]
+poll_interval = 0
+
+[screensaver]
+fps = 5000
+opacity = 7.0
+
+[monitors.DP-1]
+screensaver = "nonexistent_shader_xyz"
+opacity = -1.0
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                ("general.poll_interval".to_string(), Some((3, 1))),
+                ("screensaver.fps".to_string(), Some((6, 1))),
+                ("screensaver.opacity".to_string(), Some((7, 1))),
+                ("monitors.DP-1.screensaver".to_string(), Some((10, 1))),
+                ("monitors.DP-1.opacity".to_string(), Some((11, 1))),
+            ]
+        );
+        assert!(Config::load("/nonexistent/hyprfresh.toml").is_err());
+    }
+
+    #[test]
+    fn presets_merge_between_global_and_monitor() {
+        let config: Config = toml::from_str(
+            r#"
+            [screensaver]
+            name = "night"
+            opacity = 0.9
+
+            [screensaver.options]
+            speed = 1.0
+            density = 1.0
+
+            [presets.night]
+            screensaver = "matrix"
+            fps = 15
+
+            [presets.night.options]
+            speed = 0.3
+
+            [monitors.DP-2]
+            fps = 60
+
+            [monitors.DP-2.options]
+            density = 2.0
+            "#,
+        )
+        .unwrap();
+
+        let dp1 = config.screensaver_for("DP-1");
+        assert_eq!(dp1.name, "matrix");
+        assert_eq!((dp1.fps, dp1.opacity), (15, 0.9));
+        assert_eq!(dp1.options["speed"], toml::Value::Float(0.3));
+        assert_eq!(dp1.options["density"], toml::Value::Float(1.0));
+
+        let dp2 = config.screensaver_for("DP-2");
+        assert_eq!(dp2.fps, 60);
+        assert_eq!(dp2.options["speed"], toml::Value::Float(0.3));
+        assert_eq!(dp2.options["density"], toml::Value::Float(2.0));
+        assert_eq!(config.configured_screensaver("DP-2"), "night");
+
+        // Explicitly named savers skip the configured preset
+        let plain = config.screensaver_named("DP-1", "starfield");
+        assert_eq!((plain.name.as_str(), plain.fps), ("starfield", 30));
+    }
+
+    #[test]
+    fn check_validates_presets() {
+        let source = r#"
+[screensaver]
+name = "lobby"
+
+[presets.lobby]
+screensaver = "night"
+opacity = 2.0
+
+[presets.night]
+screensaver = "matrix"
+
+[monitors.DP-1]
+screensaver = "nope"
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                ("presets.lobby.screensaver".to_string(), Some((6, 1))),
+                ("presets.lobby.opacity".to_string(), Some((7, 1))),
+                ("monitors.DP-1.screensaver".to_string(), Some((13, 1))),
+            ]
+        );
+        let (_, diagnostics) = Config::check_source(source);
+        assert!(diagnostics[0].message.contains("other presets"));
+        assert!(diagnostics[2].message.contains("presets: lobby, night"));
+
+        let (_, diagnostics) = Config::check_source("[presets.matrix]\nscreensaver = \"matrix\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn check_validates_options_against_shader_header() {
+        let source = r#"
+[screensaver]
+name = "matrix"
+
+[screensaver.options]
+speed = 99.0
+color = "green"
+stars = 100
+
+[monitors.DP-1]
+screensaver = "starfield"
+
+[monitors.DP-1.options]
+stars = 1.5
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                ("screensaver.options.color".to_string(), Some((7, 1))),
+                ("screensaver.options.speed".to_string(), Some((6, 1))),
+                ("monitors.DP-1.options.stars".to_string(), Some((14, 1))),
+            ]
+        );
+        let (_, diagnostics) = Config::check_source(source);
+        let warning = diagnostics
+            .iter()
+            .find(|d| d.severity == Severity::Warning)
+            .unwrap();
+        assert_eq!(warning.key.join("."), "screensaver.options.stars");
+        assert!(warning.message.contains("options: speed, density, color"), "{}", warning);
+    }
+
+    #[test]
+    fn clock_is_checked_and_toggled_per_monitor() {
+        let source = r#"
+[screensaver.clock]
+enabled = true
+format = "%H:%M %Q"
+font = "/nonexistent/font.ttf"
+size = 0.0
+opacity = 2.0
+position = "top-left"
+
+[presets.plain]
+screensaver = "matrix"
+clock = false
+
+[monitors.DP-1]
+clock = false
+"#;
+        assert_eq!(
+            errors(source),
+            vec![
+                ("screensaver.clock.format".to_string(), Some((4, 1))),
+                ("screensaver.clock.font".to_string(), Some((5, 1))),
+                ("screensaver.clock.size".to_string(), Some((6, 1))),
+                ("screensaver.clock.opacity".to_string(), Some((7, 1))),
+            ]
+        );
+
+        let (config, _) = Config::check_source(source);
+        let config = config.unwrap();
+        let clock = config.screensaver_for("DP-2").clock;
+        assert!(clock.enabled);
+        assert_eq!(clock.position, ClockPosition::TopLeft);
+        assert!(!config.screensaver_for("DP-1").clock.enabled);
+        assert!(!config.screensaver_named("DP-2", "plain").clock.enabled);
+        assert_eq!(config.presets["plain"].describe(), "matrix, no clock");
+
+        let (_, diagnostics) = Config::check_source("[screensaver.clock]
+position = \"middle\"\n");
+        assert!(diagnostics[0].message.contains("unknown variant `middle`"), "{}", diagnostics[0]);
+    }
+
+    #[test]
+    fn check_outputs_warns_about_unknown_monitors() {
+        let source = "[monitors.DP-1]\nidle_timeout = 60\n\n[monitors.HDMI-A-1]\ndisabled = true\n";
+        let (config, _) = Config::check_source(source);
+        let warnings = config
+            .unwrap()
+            .check_outputs(source, &["DP-1".to_string(), "DP-2".to_string()]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(
+            warnings[0].render("hyprfresh.toml"),
+            "hyprfresh.toml:4:1: warning: monitors.HDMI-A-1: no connected output named 'HDMI-A-1' (connected: DP-1, DP-2)"
+        );
+    }
+
+    #[test]
+    fn screensaver_for_applies_monitor_override() {
+        let config: Config = toml::from_str(
+            r#"
+            [screensaver]
+            name = "matrix"
+            fps = 60
+
+            [screensaver.options]
+            speed = 1.0
+            color = [0.0, 1.0, 0.0]
+
+            [monitors.DP-1]
+            screensaver = "starfield"
+            opacity = 0.5
+
+            [monitors.DP-1.options]
+            speed = 2.0
+
+            [monitors.HDMI-A-1]
+            fps = 24
+            "#,
+        )
+        .unwrap();
+
+        let dp1 = config.screensaver_for("DP-1");
+        assert_eq!(dp1.name, "starfield");
+        assert_eq!(dp1.fps, 60);
+        assert_eq!(dp1.opacity, 0.5);
+        assert_eq!(dp1.options["speed"], toml::Value::Float(2.0));
+        assert_eq!(dp1.options["color"], config.screensaver.options["color"]);
+
+        let hdmi = config.screensaver_for("HDMI-A-1");
+        assert_eq!(hdmi.name, "matrix");
+        assert_eq!(hdmi.fps, 24);
+        assert_eq!(hdmi.opacity, 1.0);
+
+        assert_eq!(config.screensaver_for("DP-2"), config.screensaver);
+    }
 }
@@ -0,0 +1,346 @@
+//! Local control socket
+//!
+//! The daemon listens on `$XDG_RUNTIME_DIR/hyprfresh/control.sock` so that
+//! keybinds and scripts can query and drive it without restarting it.
+//!
+//! Protocol: the client connects, writes one JSON-encoded `ControlRequest`
+//! terminated by a newline, and reads back one JSON-encoded `ControlResponse`
+//! line. `hyprfresh ctl ...` is the bundled client.
+
+use crate::inhibit::{InhibitSpec, InhibitorStatus};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener as StdUnixListener, UnixStream as StdUnixStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+
+/// How long `hyprfresh ctl` waits on a daemon that has stopped answering
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Requests understood by the daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Report per-monitor idle/screensaver state
+    Status,
+    /// Start a screensaver now (all monitors if `monitor` is None)
+    Start {
+        monitor: Option<String>,
+        screensaver: Option<String>,
+    },
+    /// Stop a screensaver (all monitors if `monitor` is None)
+    Stop { monitor: Option<String> },
+    /// Suppress automatic screensaver starts (see `InhibitSpec`)
+    Inhibit(InhibitSpec),
+    /// Remove inhibitors by id, by monitor, or all when both are None
+    Uninhibit {
+        id: Option<u64>,
+        monitor: Option<String>,
+    },
+    /// Re-read the configuration file
+    Reload,
+    /// Set the away message (see `message`), or clear it when None
+    Message { text: Option<String> },
+}
+
+/// Responses sent back to the client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ControlResponse {
+    Ok,
+    Status(DaemonStatus),
+    Inhibited { id: u64 },
+    Error { message: String },
+}
+
+/// Snapshot of the daemon's idle state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// Whether ext-idle-notify currently reports the session idle
+    pub session_idle: bool,
+    /// Whether automatic starts are inhibited on every monitor
+    pub inhibited: bool,
+    pub monitors: Vec<MonitorStatus>,
+    pub inhibitors: Vec<InhibitorStatus>,
+    /// Away message set with `ctl message`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Per-monitor part of `DaemonStatus`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorStatus {
+    pub name: String,
+    /// Whether a screensaver is showing
+    pub active: bool,
+    /// Screensaver configured for this monitor
+    pub screensaver: String,
+    /// Seconds since the cursor was last on this monitor
+    pub idle_secs: u64,
+    /// Effective idle timeout for this monitor
+    pub timeout_secs: u64,
+    pub disabled: bool,
+    /// Whether an inhibitor covers this monitor
+    pub inhibited: bool,
+    /// Why a fallback is showing instead of the configured screensaver
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A request handed to the idle loop, with a channel for its answer
+#[derive(Debug)]
+pub struct ControlMessage {
+    pub request: ControlRequest,
+    pub reply: oneshot::Sender<ControlResponse>,
+}
+
+/// Path of the control socket
+pub fn socket_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let xdg_runtime =
+        std::env::var("XDG_RUNTIME_DIR").map_err(|_| "XDG_RUNTIME_DIR is not set")?;
+    Ok(PathBuf::from(format!(
+        "{}/hyprfresh/control.sock",
+        xdg_runtime
+    )))
+}
+
+/// Bind the control socket, refusing to steal it from a running daemon.
+///
+/// Returns a std listener so this can run before the tokio runtime exists;
+/// `serve` registers it with the runtime.
+pub fn bind(path: &Path) -> Result<StdUnixListener, Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+
+    if path.exists() {
+        if StdUnixStream::connect(path).is_ok() {
+            return Err(format!(
+                "another hyprfresh daemon is listening on {}",
+                path.display()
+            )
+            .into());
+        }
+        debug!("Removing stale control socket {}", path.display());
+        std::fs::remove_file(path)?;
+    }
+
+    let listener = StdUnixListener::bind(path)?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+/// Remove the control socket on shutdown
+pub fn cleanup(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        warn!("Failed to remove control socket {}: {}", path.display(), e);
+    }
+}
+
+/// Accept control connections and forward their requests to the idle loop
+pub async fn serve(listener: StdUnixListener, tx: mpsc::Sender<ControlMessage>) {
+    let listener = match UnixListener::from_std(listener) {
+        Ok(l) => l,
+        Err(e) => {
+            warn!("Failed to register control socket: {}", e);
+            return;
+        }
+    };
+    info!("Control socket listening");
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                warn!("Control socket accept failed: {}", e);
+                continue;
+            }
+        };
+
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, tx).await {
+                debug!("Control connection error: {}", e);
+            }
+        });
+    }
+}
+
+/// Serve a single request/response exchange
+async fn handle_connection(
+    stream: UnixStream,
+    tx: mpsc::Sender<ControlMessage>,
+) -> std::io::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    tokio::io::BufReader::new(read).read_line(&mut line).await?;
+
+    let response = match serde_json::from_str::<ControlRequest>(line.trim()) {
+        Ok(request) => {
+            debug!("Control request: {:?}", request);
+            dispatch(request, &tx).await
+        }
+        Err(e) => ControlResponse::Error {
+            message: format!("invalid request: {}", e),
+        },
+    };
+
+    let mut out = serde_json::to_string(&response).map_err(std::io::Error::other)?;
+    out.push('\n');
+    write.write_all(out.as_bytes()).await?;
+    write.shutdown().await
+}
+
+/// Hand a request to the idle loop and wait for its answer
+pub async fn dispatch(
+    request: ControlRequest,
+    tx: &mpsc::Sender<ControlMessage>,
+) -> ControlResponse {
+    let (reply, rx) = oneshot::channel();
+    if tx.send(ControlMessage { request, reply }).await.is_err() {
+        return ControlResponse::Error {
+            message: "daemon is shutting down".to_string(),
+        };
+    }
+    rx.await.unwrap_or_else(|_| ControlResponse::Error {
+        message: "request dropped by daemon".to_string(),
+    })
+}
+
+/// Client side: send one request to a running daemon and wait for the answer
+pub fn send_request(
+    path: &Path,
+    request: &ControlRequest,
+) -> Result<ControlResponse, Box<dyn std::error::Error>> {
+    let mut stream = StdUnixStream::connect(path).map_err(|e| {
+        format!(
+            "cannot connect to {} (is the daemon running?): {}",
+            path.display(),
+            e
+        )
+    })?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let timed_out = |e: std::io::Error| match e.kind() {
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => format!(
+            "daemon did not respond within {}s",
+            CLIENT_TIMEOUT.as_secs()
+        ),
+        _ => e.to_string(),
+    };
+
+    let mut msg = serde_json::to_string(request)?;
+    msg.push('\n');
+    stream.write_all(msg.as_bytes()).map_err(timed_out)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).map_err(timed_out)?;
+    Ok(serde_json::from_str(line.trim())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_wire_format() {
+        let json = serde_json::to_string(&ControlRequest::Start {
+            monitor: Some("DP-1".into()),
+            screensaver: None,
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"cmd":"start","monitor":"DP-1","screensaver":null}"#);
+
+        let parsed: ControlRequest = serde_json::from_str(r#"{"cmd":"status"}"#).unwrap();
+        assert_eq!(parsed, ControlRequest::Status);
+
+        // Optional fields may be omitted
+        let parsed: ControlRequest = serde_json::from_str(r#"{"cmd":"stop"}"#).unwrap();
+        assert_eq!(parsed, ControlRequest::Stop { monitor: None });
+
+        let parsed: ControlRequest =
+            serde_json::from_str(r#"{"cmd":"inhibit","duration_secs":5400,"reason":"render job"}"#)
+                .unwrap();
+        assert_eq!(
+            parsed,
+            ControlRequest::Inhibit(InhibitSpec {
+                duration_secs: Some(5400),
+                reason: Some("render job".into()),
+                ..Default::default()
+            })
+        );
+
+        let parsed: ControlRequest = serde_json::from_str(r#"{"cmd":"message"}"#).unwrap();
+        assert_eq!(parsed, ControlRequest::Message { text: None });
+    }
+
+    #[test]
+    fn response_roundtrip() {
+        let status = ControlResponse::Status(DaemonStatus {
+            session_idle: false,
+            inhibited: true,
+            monitors: vec![MonitorStatus {
+                name: "DP-1".into(),
+                active: true,
+                screensaver: "matrix".into(),
+                idle_secs: 42,
+                timeout_secs: 300,
+                disabled: false,
+                inhibited: true,
+                error: Some("'rain' failed to build: invalid shader".into()),
+            }],
+            inhibitors: vec![InhibitorStatus {
+                id: 1,
+                monitor: None,
+                reason: Some("render job".into()),
+                remaining_secs: Some(5340),
+                pid: None,
+            }],
+            message: Some("Back at 14:30".into()),
+        });
+        let json = serde_json::to_string(&status).unwrap();
+        assert_eq!(serde_json::from_str::<ControlResponse>(&json).unwrap(), status);
+    }
+
+    #[tokio::test]
+    async fn socket_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("hyprfresh_test_ctl_{}", std::process::id()));
+        let path = dir.join("control.sock");
+        let listener = bind(&path).unwrap();
+
+        // A second daemon must not steal a live socket
+        assert!(bind(&path).is_err());
+
+        let (tx, mut rx) = mpsc::channel::<ControlMessage>(4);
+        let server = tokio::spawn(serve(listener, tx));
+
+        // Stand-in for the idle loop
+        let idle = tokio::spawn(async move {
+            let msg = rx.recv().await.unwrap();
+            assert_eq!(msg.request, ControlRequest::Inhibit(InhibitSpec::default()));
+            msg.reply.send(ControlResponse::Inhibited { id: 1 }).unwrap();
+        });
+
+        let client_path = path.clone();
+        let response = tokio::task::spawn_blocking(move || {
+            send_request(&client_path, &ControlRequest::Inhibit(InhibitSpec::default())).unwrap()
+        })
+        .await
+        .unwrap();
+        assert_eq!(response, ControlResponse::Inhibited { id: 1 });
+
+        idle.await.unwrap();
+        server.abort();
+        cleanup(&path);
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
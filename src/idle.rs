@@ -15,10 +15,21 @@
 //! 3. When ext-idle-notify fires Idled → start screensaver on remaining monitors
 //! 4. When ext-idle-notify fires Resumed → stop ALL screensavers
 //! 5. Any cursor movement on a screensaver'd monitor → stop that screensaver
+//! 6. The renderer reports what it actually did (`RendererEvent`); a saver torn
+//!    down behind our back is restarted once that monitor's timeout elapses again
+//!
+//! The decision making lives in [`IdleTracker`], a pure state machine that is
+//! fed observations plus a caller-supplied timestamp and returns the renderer
+//! commands to send. [`run_idle_loop`] is only the async driver that gathers
+//! those observations from Hyprland IPC and forwards the commands.
 
 use crate::config::Config;
-use crate::ipc::{self, HyprEvent};
-use crate::renderer::RendererCommand;
+use crate::control::{ControlMessage, ControlRequest, ControlResponse, DaemonStatus, MonitorStatus};
+use crate::inhibit::{self, InhibitorRegistry};
+use crate::ipc::{self, CursorPos, HyprEvent, MonitorInfo};
+use crate::message;
+use crate::reload::ConfigReloader;
+use crate::renderer::{RendererCommand, RendererEvent, StopReason};
 use log::{debug, info, warn};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -32,376 +43,1405 @@ use tokio::time::{self, Duration};
 struct MonitorIdleState {
     /// Last time the cursor was detected on this monitor
     last_cursor_seen: Instant,
-    /// Whether the screensaver is currently showing on this monitor
+    /// Whether the screensaver is currently showing on this monitor.
+    /// Set optimistically when a Start is sent, then corrected by `RendererEvent`s.
     screensaver_active: bool,
+    /// Why the renderer is showing a fallback instead of the configured saver
+    fallback_error: Option<String>,
 }
 
-/// Run the idle detection loop
+/// Deterministic idle state machine.
 ///
-/// Polls Hyprland IPC for cursor position and monitor layout.
-/// Inactive monitors (cursor absent for `idle_timeout`) get screensavers.
-/// The active monitor (where cursor/focus is) only gets a screensaver when
-/// `session_idle_active` is set by ext-idle-notify-v1 in the renderer.
-pub async fn run_idle_loop(
+/// Holds no clock and performs no I/O: every method takes the current time
+/// from the caller and returns the `RendererCommand`s that should be sent.
+#[derive(Debug)]
+pub struct IdleTracker {
     config: Config,
-    tx: mpsc::Sender<RendererCommand>,
-    session_idle_active: Arc<AtomicBool>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let poll_interval = Duration::from_millis(config.general.poll_interval);
-    let idle_timeout = Duration::from_secs(config.general.idle_timeout);
+    monitors: HashMap<String, MonitorIdleState>,
+    last_cursor_monitor: Option<String>,
+    last_cursor_pos: Option<(i32, i32)>,
+    session_was_idle: bool,
+    /// Suppress automatic starts (added via the control socket)
+    inhibitors: InhibitorRegistry,
+    /// Away message set via the control socket
+    message: Option<String>,
+}
 
-    let mut monitor_states: HashMap<String, MonitorIdleState> = HashMap::new();
-    let mut last_cursor_monitor: Option<String> = None;
-    let mut last_cursor_pos: Option<(i32, i32)> = None;
-    let mut session_was_idle = false;
+impl IdleTracker {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            monitors: HashMap::new(),
+            last_cursor_monitor: None,
+            last_cursor_pos: None,
+            session_was_idle: false,
+            inhibitors: InhibitorRegistry::default(),
+            message: None,
+        }
+    }
 
-    info!(
-        "Idle loop started: poll={}ms, per-monitor timeout={}s",
-        config.general.poll_interval, config.general.idle_timeout
-    );
+    /// Show `text` as the away message in place of the configured one, or
+    /// go back to the configured one when None
+    pub fn set_message(&mut self, text: Option<String>) -> Vec<RendererCommand> {
+        self.message = text.clone();
+        vec![RendererCommand::SetMessage { text }]
+    }
 
-    let mut interval = time::interval(poll_interval);
+    /// Swap in a reloaded configuration.
+    ///
+    /// Returns the commands that bring the renderer in line with it: active
+    /// monitors that are now disabled are stopped, active monitors whose
+    /// effective screensaver settings changed are restarted, and the
+    /// session-wide idle notification is updated if its settings changed.
+    /// Everything else (cursor state, timers, inhibitors) carries over.
+    pub fn apply_config(&mut self, config: Config) -> Vec<RendererCommand> {
+        let old = std::mem::replace(&mut self.config, config);
+        let mut commands = Vec::new();
 
-    loop {
-        interval.tick().await;
+        let general = &self.config.general;
+        if general.session_idle != old.general.session_idle
+            || general.effective_session_idle_timeout()
+                != old.general.effective_session_idle_timeout()
+        {
+            commands.push(RendererCommand::SetSessionIdle {
+                enabled: general.session_idle,
+                timeout_secs: general.effective_session_idle_timeout(),
+            });
+        }
 
-        // Get current cursor position and monitor layout
-        let cursor = match ipc::get_cursor_pos().await {
-            Ok(c) => c,
-            Err(e) => {
-                warn!("Failed to get cursor position: {}", e);
+        let mut names: Vec<String> = self.monitors.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            if !self.is_active(&name) {
                 continue;
             }
-        };
-
-        let monitors = match ipc::get_monitors().await {
-            Ok(m) => m,
-            Err(e) => {
-                warn!("Failed to get monitors: {}", e);
-                continue;
+            if self.is_disabled(&name) {
+                info!("Monitor {} disabled by config, stopping screensaver", name);
+                if let Some(state) = self.monitors.get_mut(&name) {
+                    state.screensaver_active = false;
+                }
+                commands.push(RendererCommand::Stop {
+                    monitor: name.clone(),
+                });
+            } else if old.screensaver_for(&name) != self.config.screensaver_for(&name) {
+                info!("Screensaver settings for {} changed, restarting it", name);
+                commands.push(RendererCommand::Stop {
+                    monitor: name.clone(),
+                });
+                commands.push(self.activate(&name));
             }
-        };
+        }
+
+        commands
+    }
+
+    /// Drop inhibitors whose owning process has exited
+    pub fn prune_inhibitors(&mut self, is_alive: impl Fn(u32) -> bool) {
+        self.inhibitors.prune_dead(is_alive);
+    }
+
+    /// Whether the tracker believes a screensaver is showing on `monitor`
+    pub fn is_active(&self, monitor: &str) -> bool {
+        self.monitors
+            .get(monitor)
+            .is_some_and(|state| state.screensaver_active)
+    }
+
+    /// Feed one poll result (cursor position, monitor layout and the
+    /// session-wide idle flag) observed at `now`.
+    pub fn poll(
+        &mut self,
+        now: Instant,
+        cursor: CursorPos,
+        monitors: &[MonitorInfo],
+        session_idle: bool,
+    ) -> Vec<RendererCommand> {
+        let mut commands = Vec::new();
+        self.inhibitors.expire(now);
 
         // Detect cursor movement
-        let cursor_moved = match last_cursor_pos {
+        let cursor_moved = match self.last_cursor_pos {
             Some((lx, ly)) => cursor.x != lx || cursor.y != ly,
             None => false,
         };
-        last_cursor_pos = Some((cursor.x, cursor.y));
+        self.last_cursor_pos = Some((cursor.x, cursor.y));
 
         // Which monitor is the cursor on?
-        let current_monitor = ipc::cursor_on_monitor(&cursor, &monitors);
+        let current_monitor = ipc::cursor_on_monitor(&cursor, monitors);
 
-        // Initialize state for new monitors
-        let now = Instant::now();
-        for monitor in &monitors {
-            monitor_states
-                .entry(monitor.name.clone())
-                .or_insert(MonitorIdleState {
-                    last_cursor_seen: now,
-                    screensaver_active: false,
-                });
-        }
-
-        // Remove state for disconnected monitors
-        let connected_names: Vec<String> = monitors.iter().map(|m| m.name.clone()).collect();
-        monitor_states.retain(|name, state| {
-            if !connected_names.contains(name) {
-                if state.screensaver_active {
-                    info!("Monitor {} disconnected, sending removal command", name);
-                    let _ = tx.try_send(RendererCommand::MonitorRemoved {
-                        monitor: name.clone(),
-                    });
-                }
-                false
-            } else {
-                true
-            }
-        });
+        self.sync_monitors(now, monitors, &mut commands);
 
         // --- Cursor activity: update which monitor the cursor is on ---
         if let Some(ref name) = current_monitor
-            && let Some(state) = monitor_states.get_mut(name)
+            && let Some(state) = self.monitors.get_mut(name)
         {
-            if cursor_moved {
-                    state.last_cursor_seen = now;
-
-                    // Wake this monitor if screensaver is active
-                    if state.screensaver_active {
-                        info!("Cursor movement on {}, stopping screensaver", name);
-                        state.screensaver_active = false;
-                        if let Err(e) = tx
-                            .send(RendererCommand::Stop {
-                                monitor: name.clone(),
-                            })
-                            .await
-                        {
-                            warn!("Failed to send stop command: {}", e);
-                        }
-                    }
-            } else {
-                // Cursor is on this monitor but didn't move — still "present"
-                // (the user might be typing; ext-idle-notify handles that)
+            // Cursor is on this monitor — it's "present" even when it didn't
+            // move (the user might be typing; ext-idle-notify handles that).
+            // Once the session is idle nobody is typing, so a parked cursor
+            // no longer counts as activity.
+            if cursor_moved || !session_idle {
                 state.last_cursor_seen = now;
             }
+
+            // Wake this monitor if screensaver is active
+            if cursor_moved && state.screensaver_active {
+                info!("Cursor movement on {}, stopping screensaver", name);
+                state.screensaver_active = false;
+                commands.push(RendererCommand::Stop {
+                    monitor: name.clone(),
+                });
+            }
         }
 
         // Track monitor transitions
-        if current_monitor != last_cursor_monitor {
+        if current_monitor != self.last_cursor_monitor {
             if let Some(ref new_mon) = current_monitor {
                 debug!("Cursor moved to monitor {}", new_mon);
             }
-            last_cursor_monitor = current_monitor.clone();
+            self.last_cursor_monitor = current_monitor.clone();
         }
 
         // --- Session-wide idle (ext-idle-notify-v1) ---
-        let session_idle = session_idle_active.load(Ordering::SeqCst);
 
         // Session just went idle → start screensavers on ALL remaining monitors
-        if session_idle && !session_was_idle {
+        if session_idle && !self.session_was_idle {
             info!("Session idle detected, starting screensavers on all monitors");
-            for (name, state) in monitor_states.iter_mut() {
-                if state.screensaver_active {
-                    continue;
-                }
-                if let Some(mon_cfg) = config.monitors.get(name)
-                    && mon_cfg.disabled
+            let mut names: Vec<String> = self.monitors.keys().cloned().collect();
+            names.sort();
+            for name in names {
+                if self.is_active(&name) || self.is_disabled(&name) || self.inhibitors.blocks(&name)
                 {
                     continue;
                 }
-
-                let screensaver_name = config
-                    .monitors
-                    .get(name)
-                    .and_then(|m| m.screensaver.clone())
-                    .unwrap_or_else(|| config.screensaver.name.clone());
-
                 info!("Session idle: starting screensaver on {}", name);
-                state.screensaver_active = true;
-                if let Err(e) = tx
-                    .send(RendererCommand::Start {
-                        monitor: name.clone(),
-                        screensaver: screensaver_name,
-                    })
-                    .await
-                {
-                    warn!("Failed to send start command: {}", e);
-                }
+                commands.push(self.activate(&name));
             }
         }
 
         // Session resumed → only stop the screensaver on the cursor's monitor.
         // Non-cursor monitors keep their screensavers — they're still idle
         // (cursor isn't there). The per-monitor idle block below handles them.
-        if !session_idle && session_was_idle {
+        if !session_idle && self.session_was_idle {
             if let Some(ref name) = current_monitor {
                 info!("Session resumed, stopping screensaver on active monitor {}", name);
-                if let Some(state) = monitor_states.get_mut(name) {
+                if let Some(state) = self.monitors.get_mut(name) {
                     state.screensaver_active = false;
                     state.last_cursor_seen = now;
                 }
-                if let Err(e) = tx
-                    .send(RendererCommand::Stop {
-                        monitor: name.clone(),
-                    })
-                    .await
-                {
-                    warn!("Failed to send stop command: {}", e);
-                }
+                commands.push(RendererCommand::Stop {
+                    monitor: name.clone(),
+                });
             } else {
                 info!("Session resumed but cursor not on any monitor, stopping all");
-                for state in monitor_states.values_mut() {
+                for state in self.monitors.values_mut() {
                     state.screensaver_active = false;
                     state.last_cursor_seen = now;
                 }
-                if let Err(e) = tx.send(RendererCommand::StopAll).await {
-                    warn!("Failed to send stop-all command: {}", e);
-                }
+                commands.push(RendererCommand::StopAll);
             }
         }
 
-        session_was_idle = session_idle;
+        self.session_was_idle = session_idle;
 
         // --- Per-monitor idle: inactive monitors get screensavers early ---
-        // Only when session is NOT idle (ext-idle-notify hasn't fired yet).
-        // Once session goes idle, the block above covers everything.
-        if !session_idle {
-            for (name, state) in monitor_states.iter_mut() {
-                if state.screensaver_active {
-                    continue;
-                }
+        // While the session is idle the block above already covered every
+        // monitor; this only restarts savers the renderer lost since then.
+        let mut names: Vec<String> = self.monitors.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            if self.is_active(&name) || self.is_disabled(&name) || self.inhibitors.blocks(&name) {
+                continue;
+            }
 
-                // Skip disabled monitors
-                if let Some(mon_cfg) = config.monitors.get(name)
-                    && mon_cfg.disabled
-                {
-                    continue;
-                }
+            // Skip the monitor the cursor is currently on — that's the
+            // "active" monitor. ext-idle-notify will handle it.
+            if !session_idle && current_monitor.as_deref() == Some(name.as_str()) {
+                continue;
+            }
 
-                // Skip the monitor the cursor is currently on — that's the
-                // "active" monitor. ext-idle-notify will handle it.
-                if current_monitor.as_deref() == Some(name) {
-                    continue;
-                }
+            let idle_duration = now.duration_since(self.monitors[&name].last_cursor_seen);
 
-                // Per-monitor timeout
-                let timeout = config
-                    .monitors
-                    .get(name)
-                    .and_then(|m| m.idle_timeout)
-                    .map(Duration::from_secs)
-                    .unwrap_or(idle_timeout);
-
-                let idle_duration = now.duration_since(state.last_cursor_seen);
-
-                if idle_duration >= timeout {
-                    let screensaver_name = config
-                        .monitors
-                        .get(name)
-                        .and_then(|m| m.screensaver.clone())
-                        .unwrap_or_else(|| config.screensaver.name.clone());
-
-                    info!(
-                        "Monitor {} inactive for {:.0}s (cursor elsewhere), starting screensaver",
-                        name,
-                        idle_duration.as_secs_f64()
-                    );
-                    state.screensaver_active = true;
-                    if let Err(e) = tx
-                        .send(RendererCommand::Start {
-                            monitor: name.clone(),
-                            screensaver: screensaver_name,
-                        })
-                        .await
-                    {
-                        warn!("Failed to send start command: {}", e);
-                    }
-                }
+            if idle_duration >= self.idle_timeout_for(&name) {
+                info!(
+                    "Monitor {} inactive for {:.0}s (cursor elsewhere), starting screensaver",
+                    name,
+                    idle_duration.as_secs_f64()
+                );
+                commands.push(self.activate(&name));
             }
         }
+
+        commands
     }
-}
 
-/// Bridge Hyprland events into renderer commands.
-///
-/// Listens for events from the Hyprland event socket and translates
-/// relevant ones (monitor hotplug, focus changes) into renderer commands.
-/// Runs as a separate task alongside the idle poll loop.
-pub async fn run_event_bridge(
-    mut event_rx: mpsc::Receiver<HyprEvent>,
-    render_tx: mpsc::Sender<RendererCommand>,
-) {
-    info!("Event bridge started");
-
-    while let Some(event) = event_rx.recv().await {
+    /// Feed one Hyprland event received at `now`.
+    pub fn handle_event(&mut self, now: Instant, event: &HyprEvent) -> Vec<RendererCommand> {
         match event {
             HyprEvent::MonitorRemoved(name) => {
                 info!("Monitor removed event: {}", name);
-                let cmd = RendererCommand::MonitorRemoved { monitor: name };
-                if render_tx.send(cmd).await.is_err() {
-                    break;
-                }
+                self.monitors.remove(name);
+                vec![RendererCommand::MonitorRemoved {
+                    monitor: name.clone(),
+                }]
             }
             HyprEvent::MonitorAdded(name) => {
                 info!("Monitor added event: {}", name);
-                // The idle poll loop picks up new monitors via get_monitors()
+                // The next poll picks up new monitors via get_monitors()
+                Vec::new()
             }
             HyprEvent::FocusedMonitor(name) => {
                 debug!("Focus moved to monitor: {}", name);
+                if let Some(state) = self.monitors.get_mut(name) {
+                    state.screensaver_active = false;
+                    state.last_cursor_seen = now;
+                }
                 // Stop screensaver on the newly focused monitor (fast wake)
-                let cmd = RendererCommand::Stop { monitor: name };
-                if render_tx.send(cmd).await.is_err() {
-                    break;
+                vec![RendererCommand::Stop {
+                    monitor: name.clone(),
+                }]
+            }
+            HyprEvent::Workspace(_) | HyprEvent::Other(_) => Vec::new(),
+        }
+    }
+
+    /// Feed one lifecycle event reported by the renderer at `now`.
+    ///
+    /// The renderer is the source of truth for whether a saver is showing.
+    /// Savers that went away without us asking restart only after the
+    /// monitor's idle timeout elapses again, so a surface the compositor keeps
+    /// closing can't turn into a restart loop.
+    pub fn handle_renderer_event(&mut self, now: Instant, event: &RendererEvent) {
+        match event {
+            RendererEvent::Started { monitor } => {
+                debug!("Renderer confirmed screensaver on {}", monitor);
+                if let Some(state) = self.monitors.get_mut(monitor) {
+                    state.screensaver_active = true;
+                }
+            }
+            RendererEvent::Stopped { monitor, reason } => {
+                if let Some(state) = self.monitors.get_mut(monitor) {
+                    if state.screensaver_active && *reason != StopReason::Requested {
+                        info!("Screensaver on {} stopped by renderer ({:?})", monitor, reason);
+                        state.last_cursor_seen = now;
+                    }
+                    state.screensaver_active = false;
+                    state.fallback_error = None;
                 }
             }
-            HyprEvent::Workspace(_) | HyprEvent::Other(_) => {}
+            RendererEvent::Failed { monitor, error } => {
+                warn!("Screensaver on {} failed: {}", monitor, error);
+                if let Some(state) = self.monitors.get_mut(monitor) {
+                    state.screensaver_active = false;
+                    state.fallback_error = None;
+                    state.last_cursor_seen = now;
+                }
+            }
+            RendererEvent::Degraded { monitor, error } => {
+                warn!("Screensaver on {} replaced by a fallback: {}", monitor, error);
+                if let Some(state) = self.monitors.get_mut(monitor) {
+                    state.fallback_error = Some(error.clone());
+                }
+            }
+        }
+    }
+
+    /// Explicitly start a screensaver, bypassing timeouts, `disabled` and
+    /// inhibition. `monitor = None` targets every known monitor; `screensaver
+    /// = None` uses the configured one, otherwise it names a screensaver or
+    /// preset. A monitor that is already showing a saver is restarted so a
+    /// different saver can be swapped in.
+    pub fn start(
+        &mut self,
+        monitor: Option<&str>,
+        screensaver: Option<&str>,
+    ) -> Result<Vec<RendererCommand>, String> {
+        if let Some(requested) = screensaver {
+            self.config.resolve_name(requested)?;
+        }
+        let mut commands = Vec::new();
+        for name in self.targets(monitor)? {
+            if self.is_active(&name) {
+                commands.push(RendererCommand::Stop {
+                    monitor: name.clone(),
+                });
+            }
+            let mut cmd = self.activate(&name);
+            if let (RendererCommand::Start { screensaver: s, .. }, Some(requested)) =
+                (&mut cmd, screensaver)
+            {
+                *s = self.config.screensaver_named(&name, requested);
+            }
+            commands.push(cmd);
+        }
+        Ok(commands)
+    }
+
+    /// Explicitly stop a screensaver. The monitor's idle timer restarts from
+    /// `now`, as if the user had been there.
+    pub fn stop(&mut self, now: Instant, monitor: Option<&str>) -> Result<Vec<RendererCommand>, String> {
+        let mut commands = Vec::new();
+        for name in self.targets(monitor)? {
+            if let Some(state) = self.monitors.get_mut(&name) {
+                state.screensaver_active = false;
+                state.last_cursor_seen = now;
+            }
+            commands.push(RendererCommand::Stop { monitor: name });
+        }
+        Ok(commands)
+    }
+
+    /// Answer a control request. `Reload` needs file I/O and is handled by
+    /// the driver before it gets here.
+    pub fn handle_control(
+        &mut self,
+        now: Instant,
+        request: &ControlRequest,
+    ) -> (ControlResponse, Vec<RendererCommand>) {
+        let result = match request {
+            ControlRequest::Status => return (ControlResponse::Status(self.status(now)), Vec::new()),
+            ControlRequest::Start {
+                monitor,
+                screensaver,
+            } => self.start(monitor.as_deref(), screensaver.as_deref()),
+            ControlRequest::Stop { monitor } => self.stop(now, monitor.as_deref()),
+            ControlRequest::Inhibit(spec) => {
+                if let Some(ref monitor) = spec.monitor
+                    && let Err(e) = self.targets(Some(monitor))
+                {
+                    return (ControlResponse::Error { message: e }, Vec::new());
+                }
+                if let Some(pid) = spec.pid
+                    && !inhibit::process_alive(pid)
+                {
+                    let message = format!("process {} is not running", pid);
+                    return (ControlResponse::Error { message }, Vec::new());
+                }
+                let response = match self.inhibitors.add(now, spec.clone()) {
+                    Ok(id) => ControlResponse::Inhibited { id },
+                    Err(message) => ControlResponse::Error { message },
+                };
+                return (response, Vec::new());
+            }
+            ControlRequest::Uninhibit { id, monitor } => {
+                match self.inhibitors.remove(*id, monitor.as_deref()) {
+                    0 => Err("no matching inhibitor".to_string()),
+                    _ => Ok(Vec::new()),
+                }
+            }
+            ControlRequest::Reload => Err("reload is not handled by the tracker".to_string()),
+            ControlRequest::Message { text } => match text.as_deref().map(message::check) {
+                Some(Err(e)) => Err(e),
+                _ => Ok(self.set_message(text.clone())),
+            },
+        };
+        match result {
+            Ok(commands) => (ControlResponse::Ok, commands),
+            Err(message) => (ControlResponse::Error { message }, Vec::new()),
+        }
+    }
+
+    /// Snapshot of the tracker's state for `hyprfresh ctl status`
+    pub fn status(&self, now: Instant) -> DaemonStatus {
+        let mut monitors: Vec<MonitorStatus> = self
+            .monitors
+            .iter()
+            .map(|(name, state)| MonitorStatus {
+                name: name.clone(),
+                active: state.screensaver_active,
+                screensaver: self.screensaver_for(name),
+                idle_secs: now.duration_since(state.last_cursor_seen).as_secs(),
+                timeout_secs: self.idle_timeout_for(name).as_secs(),
+                disabled: self.is_disabled(name),
+                inhibited: self.inhibitors.blocks(name),
+                error: state.fallback_error.clone(),
+            })
+            .collect();
+        monitors.sort_by(|a, b| a.name.cmp(&b.name));
+
+        DaemonStatus {
+            session_idle: self.session_was_idle,
+            inhibited: self.inhibitors.blocks_all(),
+            monitors,
+            inhibitors: self.inhibitors.list(now),
+            message: self.message.clone(),
+        }
+    }
+
+    /// Resolve a control target to monitor names (None = all, sorted)
+    fn targets(&self, monitor: Option<&str>) -> Result<Vec<String>, String> {
+        match monitor {
+            Some(name) if self.monitors.contains_key(name) => Ok(vec![name.to_string()]),
+            Some(name) => {
+                let mut known: Vec<&str> = self.monitors.keys().map(String::as_str).collect();
+                known.sort();
+                Err(format!("unknown monitor '{}' (known: {})", name, known.join(", ")))
+            }
+            None => {
+                let mut names: Vec<String> = self.monitors.keys().cloned().collect();
+                names.sort();
+                Ok(names)
+            }
+        }
+    }
+
+    /// Add state for newly connected monitors and drop disconnected ones
+    fn sync_monitors(
+        &mut self,
+        now: Instant,
+        monitors: &[MonitorInfo],
+        commands: &mut Vec<RendererCommand>,
+    ) {
+        for monitor in monitors {
+            self.monitors
+                .entry(monitor.name.clone())
+                .or_insert(MonitorIdleState {
+                    last_cursor_seen: now,
+                    screensaver_active: false,
+                    fallback_error: None,
+                });
+        }
+
+        self.monitors.retain(|name, state| {
+            if monitors.iter().any(|m| &m.name == name) {
+                return true;
+            }
+            if state.screensaver_active {
+                info!("Monitor {} disconnected, sending removal command", name);
+                commands.push(RendererCommand::MonitorRemoved {
+                    monitor: name.clone(),
+                });
+            }
+            false
+        });
+    }
+
+    /// Mark a monitor active and build its Start command
+    fn activate(&mut self, name: &str) -> RendererCommand {
+        if let Some(state) = self.monitors.get_mut(name) {
+            state.screensaver_active = true;
+        }
+        RendererCommand::Start {
+            monitor: name.to_string(),
+            screensaver: self.config.screensaver_for(name),
+        }
+    }
+
+    fn is_disabled(&self, name: &str) -> bool {
+        self.config.monitors.get(name).is_some_and(|m| m.disabled)
+    }
+
+    fn idle_timeout_for(&self, name: &str) -> Duration {
+        let secs = self
+            .config
+            .monitors
+            .get(name)
+            .and_then(|m| m.idle_timeout)
+            .unwrap_or(self.config.general.idle_timeout);
+        Duration::from_secs(secs)
+    }
+
+    fn screensaver_for(&self, name: &str) -> String {
+        self.config.configured_screensaver(name).to_string()
+    }
+}
+
+/// Everything the idle loop listens to besides its own poll timer
+pub struct IdleInputs {
+    /// Hyprland socket2 events
+    pub hypr_events: mpsc::Receiver<HyprEvent>,
+    /// Surface lifecycle events from the renderer
+    pub renderer_events: mpsc::UnboundedReceiver<RendererEvent>,
+    /// Requests from the control socket
+    pub control: mpsc::Receiver<ControlMessage>,
+    /// Set by ext-idle-notify-v1 in the renderer
+    pub session_idle_active: Arc<AtomicBool>,
+}
+
+/// Run the idle detection loop
+///
+/// Polls Hyprland IPC for cursor position and monitor layout, and listens for
+/// Hyprland events (monitor hotplug, focus changes), renderer lifecycle events
+/// and control requests. All are fed into an [`IdleTracker`] and the resulting
+/// commands are forwarded to the renderer.
+/// The active monitor (where cursor/focus is) only gets a screensaver when
+/// `session_idle_active` is set by ext-idle-notify-v1 in the renderer.
+pub async fn run_idle_loop(
+    config: Config,
+    config_path: String,
+    tx: mpsc::Sender<RendererCommand>,
+    inputs: IdleInputs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let IdleInputs {
+        mut hypr_events,
+        mut renderer_events,
+        mut control,
+        session_idle_active,
+    } = inputs;
+    let mut poll_interval = Duration::from_millis(config.general.poll_interval);
+
+    info!(
+        "Idle loop started: poll={}ms, per-monitor timeout={}s",
+        config.general.poll_interval, config.general.idle_timeout
+    );
+
+    let mut tracker = IdleTracker::new(config);
+    let mut reloader = ConfigReloader::new(&config_path);
+
+    // The away message from before the restart
+    let message_path = message::state_path();
+    if let Some(ref path) = message_path
+        && let Some(text) = message::load(path)
+    {
+        info!("Restored away message from {}", path.display());
+        for cmd in tracker.set_message(Some(text)) {
+            tx.send(cmd).await?;
         }
     }
+    let mut interval = time::interval(poll_interval);
+    let mut events_open = true;
+
+    loop {
+        let commands = tokio::select! {
+            _ = interval.tick() => {
+                // Get current cursor position and monitor layout
+                let cursor = match ipc::get_cursor_pos().await {
+                    Ok(c) => c,
+                    Err(e) => {
+                        warn!("Failed to get cursor position: {}", e);
+                        continue;
+                    }
+                };
 
-    info!("Event bridge stopped");
+                let monitors = match ipc::get_monitors().await {
+                    Ok(m) => m,
+                    Err(e) => {
+                        warn!("Failed to get monitors: {}", e);
+                        continue;
+                    }
+                };
+
+                tracker.prune_inhibitors(inhibit::process_alive);
+                let session_idle = session_idle_active.load(Ordering::SeqCst);
+                tracker.poll(Instant::now(), cursor, &monitors, session_idle)
+            }
+            event = hypr_events.recv(), if events_open => match event {
+                Some(event) => tracker.handle_event(Instant::now(), &event),
+                None => {
+                    info!("Hyprland event stream closed");
+                    events_open = false;
+                    continue;
+                }
+            },
+            Some(status) = renderer_events.recv() => {
+                tracker.handle_renderer_event(Instant::now(), &status);
+                continue;
+            }
+            Some(msg) = control.recv() => {
+                let (response, commands) = match msg.request {
+                    ControlRequest::Reload => match reloader.reload() {
+                        Ok(config) => {
+                            info!("Reloaded configuration from {}", reloader.path());
+                            let new_interval = Duration::from_millis(config.general.poll_interval);
+                            if new_interval != poll_interval {
+                                poll_interval = new_interval;
+                                interval = time::interval(poll_interval);
+                            }
+                            (ControlResponse::Ok, tracker.apply_config(config))
+                        }
+                        Err(e) => {
+                            warn!("Config reload rejected, keeping the old configuration: {}", e);
+                            (ControlResponse::Error { message: e }, Vec::new())
+                        }
+                    },
+                    ControlRequest::Message { ref text } => {
+                        let (response, commands) = tracker.handle_control(Instant::now(), &msg.request);
+                        // Shown either way; only the next restart forgets it
+                        let saved = match message_path {
+                            _ if response != ControlResponse::Ok => Ok(()),
+                            Some(ref path) => message::save(path, text.as_deref()),
+                            None => Err("message not saved: HOME is not set".to_string()),
+                        };
+                        match saved {
+                            Ok(()) => (response, commands),
+                            Err(message) => {
+                                warn!("{}", message);
+                                (ControlResponse::Error { message }, commands)
+                            }
+                        }
+                    }
+                    ref request => tracker.handle_control(Instant::now(), request),
+                };
+                let _ = msg.reply.send(response);
+                commands
+            }
+        };
+
+        for cmd in commands {
+            if let Err(e) = tx.send(cmd).await {
+                warn!("Failed to send renderer command: {}", e);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ScreensaverConfig;
+    use crate::inhibit::InhibitSpec;
 
-    /// Verify the event bridge translates MonitorRemoved into a RendererCommand
-    #[tokio::test]
-    async fn event_bridge_monitor_removed() {
-        let (event_tx, event_rx) = mpsc::channel(8);
-        let (render_tx, mut render_rx) = mpsc::channel(8);
+    fn monitor(name: &str, x: i32) -> MonitorInfo {
+        MonitorInfo {
+            id: 0,
+            name: name.to_string(),
+            x,
+            y: 0,
+            width: 1920,
+            height: 1080,
+            transform: 0,
+            active_workspace_id: 1,
+            focused: false,
+        }
+    }
 
-        let bridge = tokio::spawn(async move {
-            run_event_bridge(event_rx, render_tx).await;
-        });
+    /// Two side-by-side 1920x1080 monitors: DP-1 at x=0, DP-2 at x=1920
+    fn two_monitors() -> Vec<MonitorInfo> {
+        vec![monitor("DP-1", 0), monitor("DP-2", 1920)]
+    }
+
+    fn config(toml_src: &str) -> Config {
+        toml::from_str(toml_src).unwrap()
+    }
 
-        event_tx
-            .send(HyprEvent::MonitorRemoved("DP-1".to_string()))
-            .await
-            .unwrap();
-        drop(event_tx);
+    const ON_DP1: CursorPos = CursorPos { x: 100, y: 100 };
+    const ON_DP1_MOVED: CursorPos = CursorPos { x: 200, y: 100 };
+    const ON_DP2: CursorPos = CursorPos { x: 2000, y: 100 };
+    const OFF_SCREEN: CursorPos = CursorPos { x: -5000, y: -5000 };
 
-        let cmd = render_rx.recv().await.unwrap();
-        match cmd {
-            RendererCommand::MonitorRemoved { monitor } => assert_eq!(monitor, "DP-1"),
-            other => panic!("Expected MonitorRemoved, got {:?}", other),
+    fn start(monitor: &str, screensaver: &str) -> RendererCommand {
+        RendererCommand::Start {
+            monitor: monitor.into(),
+            screensaver: ScreensaverConfig {
+                name: screensaver.into(),
+                ..Default::default()
+            },
         }
+    }
 
-        bridge.await.unwrap();
+    fn stop(monitor: &str) -> RendererCommand {
+        RendererCommand::Stop {
+            monitor: monitor.into(),
+        }
     }
 
-    /// Verify the event bridge translates FocusedMonitor into a Stop command
-    #[tokio::test]
-    async fn event_bridge_focus_sends_stop() {
-        let (event_tx, event_rx) = mpsc::channel(8);
-        let (render_tx, mut render_rx) = mpsc::channel(8);
+    #[test]
+    fn inactive_monitor_starts_after_timeout() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
 
-        let bridge = tokio::spawn(async move {
-            run_event_bridge(event_rx, render_tx).await;
-        });
+        let cases: &[(u64, Vec<RendererCommand>)] = &[
+            (0, vec![]),
+            (5, vec![]),
+            (9, vec![]),
+            (10, vec![start("DP-2", "matrix")]),
+            // Already active: no duplicate Start
+            (11, vec![]),
+        ];
+
+        for (secs, expected) in cases {
+            let cmds = tracker.poll(t0 + Duration::from_secs(*secs), ON_DP1, &monitors, false);
+            assert_eq!(&cmds, expected, "at t={}s", secs);
+        }
+        assert!(tracker.is_active("DP-2"));
+        assert!(!tracker.is_active("DP-1"));
+    }
+
+    #[test]
+    fn cursor_movement_wakes_monitor() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        let cmds = tracker.poll(t0 + Duration::from_secs(10), ON_DP1, &monitors, false);
+        assert_eq!(cmds, vec![start("DP-2", "matrix")]);
+
+        // Cursor moves onto DP-2 → wake it; DP-1 is now the inactive one
+        let cmds = tracker.poll(t0 + Duration::from_secs(11), ON_DP2, &monitors, false);
+        assert_eq!(cmds, vec![stop("DP-2")]);
+        assert!(!tracker.is_active("DP-2"));
+    }
+
+    #[test]
+    fn per_monitor_overrides() {
+        let cfg = config(
+            r#"
+            [general]
+            idle_timeout = 100
+
+            [screensaver]
+            name = "plasmula"
+
+            [monitors.DP-2]
+            idle_timeout = 5
+            screensaver = "starfield"
+
+            [monitors.DP-3]
+            disabled = true
+            "#,
+        );
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = vec![
+            monitor("DP-1", 0),
+            monitor("DP-2", 1920),
+            monitor("DP-3", 3840),
+        ];
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        let cmds = tracker.poll(t0 + Duration::from_secs(5), ON_DP1, &monitors, false);
+        assert_eq!(cmds, vec![start("DP-2", "starfield")]);
+
+        // DP-3 is disabled, even long after its timeout
+        let cmds = tracker.poll(t0 + Duration::from_secs(500), ON_DP1, &monitors, false);
+        assert!(cmds.is_empty());
+        assert!(!tracker.is_active("DP-3"));
+    }
+
+    #[test]
+    fn session_idle_covers_remaining_monitors() {
+        let cfg = config(
+            r#"
+            [general]
+            idle_timeout = 10
+
+            [monitors.DP-2]
+            disabled = true
+            "#,
+        );
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = vec![
+            monitor("DP-1", 0),
+            monitor("DP-2", 1920),
+            monitor("DP-3", 3840),
+        ];
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        let cmds = tracker.poll(t0 + Duration::from_secs(10), ON_DP1, &monitors, false);
+        assert_eq!(cmds, vec![start("DP-3", "matrix")]);
+
+        let cmds = tracker.poll(t0 + Duration::from_secs(11), ON_DP1, &monitors, true);
+        assert_eq!(cmds, vec![start("DP-1", "matrix")]);
+
+        // Staying idle doesn't re-send
+        let cmds = tracker.poll(t0 + Duration::from_secs(12), ON_DP1, &monitors, true);
+        assert!(cmds.is_empty());
+    }
+
+    #[test]
+    fn session_resume_stops_cursor_monitor_only() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        let cmds = tracker.poll(t0 + Duration::from_secs(1), ON_DP1, &monitors, true);
+        assert_eq!(cmds, vec![start("DP-1", "matrix"), start("DP-2", "matrix")]);
+
+        let cmds = tracker.poll(t0 + Duration::from_secs(2), ON_DP1, &monitors, false);
+        assert_eq!(cmds, vec![stop("DP-1")]);
+        assert!(tracker.is_active("DP-2"));
+    }
+
+    #[test]
+    fn session_resume_with_cursor_off_screen_stops_all() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        tracker.poll(t0 + Duration::from_secs(1), ON_DP1, &monitors, true);
+
+        let cmds = tracker.poll(t0 + Duration::from_secs(2), OFF_SCREEN, &monitors, false);
+        assert_eq!(cmds, vec![RendererCommand::StopAll]);
+        assert!(!tracker.is_active("DP-1"));
+        assert!(!tracker.is_active("DP-2"));
+
+        // Idle timers were reset by the resume, so nothing restarts right away
+        let cmds = tracker.poll(t0 + Duration::from_secs(3), OFF_SCREEN, &monitors, false);
+        assert!(cmds.is_empty());
+    }
+
+    #[test]
+    fn monitor_disconnect_mid_screensaver() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &two_monitors(), false);
+        let cmds = tracker.poll(t0 + Duration::from_secs(10), ON_DP1, &two_monitors(), false);
+        assert_eq!(cmds, vec![start("DP-2", "matrix")]);
+
+        let only_dp1 = vec![monitor("DP-1", 0)];
+        let cmds = tracker.poll(t0 + Duration::from_secs(11), ON_DP1, &only_dp1, false);
+        assert_eq!(
+            cmds,
+            vec![RendererCommand::MonitorRemoved {
+                monitor: "DP-2".into()
+            }]
+        );
+        assert!(!tracker.is_active("DP-2"));
+
+        // Reconnecting starts a fresh idle timer
+        let cmds = tracker.poll(t0 + Duration::from_secs(12), ON_DP1, &two_monitors(), false);
+        assert!(cmds.is_empty());
+        let cmds = tracker.poll(t0 + Duration::from_secs(22), ON_DP1, &two_monitors(), false);
+        assert_eq!(cmds, vec![start("DP-2", "matrix")]);
+    }
 
-        event_tx
-            .send(HyprEvent::FocusedMonitor("HDMI-A-1".to_string()))
-            .await
-            .unwrap();
-        drop(event_tx);
+    #[test]
+    fn disconnect_of_inactive_monitor_is_silent() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let t0 = Instant::now();
 
-        let cmd = render_rx.recv().await.unwrap();
-        match cmd {
-            RendererCommand::Stop { monitor } => assert_eq!(monitor, "HDMI-A-1"),
-            other => panic!("Expected Stop, got {:?}", other),
+        tracker.poll(t0, ON_DP1, &two_monitors(), false);
+        let cmds = tracker.poll(t0 + Duration::from_secs(1), ON_DP1, &[monitor("DP-1", 0)], false);
+        assert!(cmds.is_empty());
+    }
+
+    #[test]
+    fn renderer_teardown_restarts_after_timeout() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        tracker.poll(at(0), ON_DP1, &monitors, false);
+        assert_eq!(tracker.poll(at(10), ON_DP1, &monitors, false), vec![start("DP-2", "matrix")]);
+        tracker.handle_renderer_event(at(10), &RendererEvent::Started { monitor: "DP-2".into() });
+        assert!(tracker.is_active("DP-2"));
+
+        let lost: &[RendererEvent] = &[
+            RendererEvent::Stopped {
+                monitor: "DP-2".into(),
+                reason: StopReason::Closed,
+            },
+            RendererEvent::Failed {
+                monitor: "DP-2".into(),
+                error: "surface error: Lost".into(),
+            },
+        ];
+
+        for (i, event) in lost.iter().enumerate() {
+            let base = 20 + i as u64 * 20;
+            tracker.handle_renderer_event(at(base), event);
+            assert!(!tracker.is_active("DP-2"), "after {:?}", event);
+            assert!(tracker.poll(at(base + 1), ON_DP1, &monitors, false).is_empty());
+            assert_eq!(
+                tracker.poll(at(base + 10), ON_DP1, &monitors, false),
+                vec![start("DP-2", "matrix")],
+                "after {:?}",
+                event
+            );
         }
+    }
 
-        bridge.await.unwrap();
+    #[test]
+    fn degraded_saver_stays_active_and_shows_in_status() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        tracker.poll(at(0), ON_DP1, &monitors, false);
+        tracker.poll(at(10), ON_DP1, &monitors, false);
+        tracker.handle_renderer_event(
+            at(10),
+            &RendererEvent::Degraded {
+                monitor: "DP-2".into(),
+                error: "'matrix' failed to build".into(),
+            },
+        );
+        tracker.handle_renderer_event(at(10), &RendererEvent::Started { monitor: "DP-2".into() });
+        assert!(tracker.is_active("DP-2"));
+        let error = |tracker: &IdleTracker| tracker.status(at(11)).monitors[1].error.clone();
+        assert_eq!(error(&tracker).as_deref(), Some("'matrix' failed to build"));
+
+        tracker.handle_renderer_event(
+            at(12),
+            &RendererEvent::Stopped {
+                monitor: "DP-2".into(),
+                reason: StopReason::Requested,
+            },
+        );
+        assert_eq!(error(&tracker), None);
     }
 
-    /// Verify workspace and unknown events don't produce renderer commands
-    #[tokio::test]
-    async fn event_bridge_ignores_workspace() {
-        let (event_tx, event_rx) = mpsc::channel(8);
-        let (render_tx, mut render_rx) = mpsc::channel(8);
+    #[test]
+    fn requested_stop_does_not_reset_timer() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
 
-        let bridge = tokio::spawn(async move {
-            run_event_bridge(event_rx, render_tx).await;
-        });
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        tracker.poll(t0 + Duration::from_secs(10), ON_DP1, &monitors, false);
+        tracker.handle_renderer_event(
+            t0 + Duration::from_secs(11),
+            &RendererEvent::Stopped {
+                monitor: "DP-2".into(),
+                reason: StopReason::Requested,
+            },
+        );
+        assert!(!tracker.is_active("DP-2"));
+        // Still idle, so it comes straight back on the next poll
+        let cmds = tracker.poll(t0 + Duration::from_secs(12), ON_DP1, &monitors, false);
+        assert_eq!(cmds, vec![start("DP-2", "matrix")]);
+    }
+
+    #[test]
+    fn session_idle_restarts_lost_cursor_monitor() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        tracker.poll(at(0), ON_DP1, &monitors, false);
+        let cmds = tracker.poll(at(1), ON_DP1, &monitors, true);
+        assert_eq!(cmds, vec![start("DP-1", "matrix"), start("DP-2", "matrix")]);
+
+        tracker.handle_renderer_event(
+            at(2),
+            &RendererEvent::Stopped {
+                monitor: "DP-1".into(),
+                reason: StopReason::Closed,
+            },
+        );
+        // Parked cursor doesn't count as activity while the session is idle
+        assert!(tracker.poll(at(5), ON_DP1, &monitors, true).is_empty());
+        assert_eq!(tracker.poll(at(12), ON_DP1, &monitors, true), vec![start("DP-1", "matrix")]);
+    }
+
+    #[test]
+    fn inhibit_suppresses_automatic_starts() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        tracker.poll(at(0), ON_DP1, &monitors, false);
+        let (response, cmds) =
+            tracker.handle_control(at(1), &ControlRequest::Inhibit(InhibitSpec::default()));
+        assert_eq!(response, ControlResponse::Inhibited { id: 1 });
+        assert!(cmds.is_empty());
+
+        assert!(tracker.poll(at(10), ON_DP1, &monitors, false).is_empty());
+        assert!(tracker.poll(at(11), ON_DP1, &monitors, true).is_empty());
+        assert!(tracker.status(at(11)).inhibited);
+
+        let (response, _) = tracker.handle_control(
+            at(12),
+            &ControlRequest::Uninhibit {
+                id: None,
+                monitor: None,
+            },
+        );
+        assert_eq!(response, ControlResponse::Ok);
+        assert_eq!(
+            tracker.poll(at(13), ON_DP1, &monitors, true),
+            vec![start("DP-2", "matrix")]
+        );
+    }
+
+    #[test]
+    fn timed_monitor_inhibitor() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = vec![
+            monitor("DP-1", 0),
+            monitor("DP-2", 1920),
+            monitor("DP-3", 3840),
+        ];
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
 
-        event_tx
-            .send(HyprEvent::Workspace("3".to_string()))
-            .await
-            .unwrap();
-        event_tx
-            .send(HyprEvent::Other("openwindow>>data".to_string()))
-            .await
-            .unwrap();
-        event_tx
-            .send(HyprEvent::MonitorAdded("DP-2".to_string()))
-            .await
-            .unwrap();
-        drop(event_tx);
-
-        bridge.await.unwrap();
-
-        // None of those events should produce a renderer command
-        assert!(render_rx.try_recv().is_err());
+        tracker.poll(at(0), ON_DP1, &monitors, false);
+        let spec = InhibitSpec {
+            monitor: Some("DP-2".into()),
+            duration_secs: Some(60),
+            reason: Some("render job".into()),
+            pid: None,
+        };
+        tracker.handle_control(at(0), &ControlRequest::Inhibit(spec));
+
+        // Only DP-3 starts while DP-2 is inhibited
+        assert_eq!(tracker.poll(at(10), ON_DP1, &monitors, false), vec![start("DP-3", "matrix")]);
+
+        let status = tracker.status(at(30));
+        assert!(!status.inhibited);
+        assert!(status.monitors[1].inhibited);
+        assert_eq!(status.inhibitors.len(), 1);
+        assert_eq!(status.inhibitors[0].remaining_secs, Some(30));
+        assert_eq!(status.inhibitors[0].reason.as_deref(), Some("render job"));
+
+        assert!(tracker.poll(at(59), ON_DP1, &monitors, false).is_empty());
+        assert_eq!(tracker.poll(at(60), ON_DP1, &monitors, false), vec![start("DP-2", "matrix")]);
+        assert!(tracker.status(at(60)).inhibitors.is_empty());
+    }
+
+    #[test]
+    fn inhibit_rejects_unknown_monitor_dead_owner_and_huge_duration() {
+        let mut tracker = IdleTracker::new(Config::default());
+        let now = Instant::now();
+        tracker.poll(now, ON_DP1, &two_monitors(), false);
+
+        let bad_monitor = InhibitSpec {
+            monitor: Some("HDMI-A-9".into()),
+            ..Default::default()
+        };
+        let (response, _) = tracker.handle_control(now, &ControlRequest::Inhibit(bad_monitor));
+        assert!(matches!(response, ControlResponse::Error { .. }));
+
+        let dead_owner = InhibitSpec {
+            pid: Some(u32::MAX),
+            ..Default::default()
+        };
+        let (response, _) = tracker.handle_control(now, &ControlRequest::Inhibit(dead_owner));
+        assert!(matches!(response, ControlResponse::Error { .. }));
+
+        let forever = InhibitSpec {
+            duration_secs: Some(u64::MAX),
+            ..Default::default()
+        };
+        let (response, _) = tracker.handle_control(now, &ControlRequest::Inhibit(forever));
+        assert!(matches!(response, ControlResponse::Error { .. }));
+        assert!(tracker.status(now).inhibitors.is_empty());
+    }
+
+    #[test]
+    fn control_start_and_stop() {
+        let cfg = config("[general]\nidle_timeout = 10\n\n[monitors.DP-2]\ndisabled = true\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        tracker.poll(at(0), ON_DP1, &monitors, false);
+
+        // Explicit start ignores `disabled` and the cursor monitor rule
+        let (response, cmds) = tracker.handle_control(
+            at(1),
+            &ControlRequest::Start {
+                monitor: None,
+                screensaver: None,
+            },
+        );
+        assert_eq!(response, ControlResponse::Ok);
+        assert_eq!(cmds, vec![start("DP-1", "matrix"), start("DP-2", "matrix")]);
+
+        // Swapping the saver on an active monitor restarts it
+        let (_, cmds) = tracker.handle_control(
+            at(2),
+            &ControlRequest::Start {
+                monitor: Some("DP-1".into()),
+                screensaver: Some("starfield".into()),
+            },
+        );
+        assert_eq!(cmds, vec![stop("DP-1"), start("DP-1", "starfield")]);
+
+        let (_, cmds) = tracker.handle_control(
+            at(3),
+            &ControlRequest::Stop {
+                monitor: Some("DP-1".into()),
+            },
+        );
+        assert_eq!(cmds, vec![stop("DP-1")]);
+        assert!(!tracker.is_active("DP-1"));
+        assert!(tracker.is_active("DP-2"));
+
+        let (response, cmds) = tracker.handle_control(
+            at(4),
+            &ControlRequest::Stop {
+                monitor: Some("HDMI-A-9".into()),
+            },
+        );
+        assert!(matches!(response, ControlResponse::Error { .. }));
+        assert!(cmds.is_empty());
+    }
+
+    #[test]
+    fn presets_resolve_for_automatic_and_control_starts() {
+        let cfg = config(
+            "[general]\nidle_timeout = 10\n\n\
+             [presets.lobby]\nscreensaver = \"matrix\"\nfps = 60\n\n\
+             [presets.lobby.options]\ncolor = [1.0, 0.0, 0.0]\n\n\
+             [monitors.DP-2]\nscreensaver = \"lobby\"\n",
+        );
+        let lobby = ScreensaverConfig {
+            name: "matrix".into(),
+            fps: 60,
+            options: HashMap::from([(
+                "color".to_string(),
+                toml::Value::Array(vec![1.0.into(), 0.0.into(), 0.0.into()]),
+            )]),
+            ..Default::default()
+        };
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        let cmds = tracker.poll(t0 + Duration::from_secs(10), ON_DP1, &monitors, false);
+        assert_eq!(
+            cmds,
+            vec![RendererCommand::Start {
+                monitor: "DP-2".into(),
+                screensaver: lobby.clone(),
+            }]
+        );
+        let status = tracker.status(t0 + Duration::from_secs(10));
+        assert_eq!(status.monitors[1].screensaver, "lobby");
+
+        let (response, cmds) = tracker.handle_control(
+            t0 + Duration::from_secs(11),
+            &ControlRequest::Start {
+                monitor: Some("DP-1".into()),
+                screensaver: Some("lobby".into()),
+            },
+        );
+        assert_eq!(response, ControlResponse::Ok);
+        assert_eq!(
+            cmds,
+            vec![RendererCommand::Start {
+                monitor: "DP-1".into(),
+                screensaver: lobby,
+            }]
+        );
+
+        let (response, cmds) = tracker.handle_control(
+            t0 + Duration::from_secs(12),
+            &ControlRequest::Start {
+                monitor: None,
+                screensaver: Some("nonexistent_shader_xyz".into()),
+            },
+        );
+        assert!(matches!(response, ControlResponse::Error { .. }));
+        assert!(cmds.is_empty());
+    }
+
+    #[test]
+    fn status_reports_monitors() {
+        let cfg = config("[general]\nidle_timeout = 10\n\n[monitors.DP-2]\nidle_timeout = 30\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &two_monitors(), false);
+        let (response, _) = tracker.handle_control(t0 + Duration::from_secs(7), &ControlRequest::Status);
+        let ControlResponse::Status(status) = response else {
+            panic!("Expected Status, got {:?}", response);
+        };
+        assert!(!status.session_idle);
+        assert_eq!(status.monitors.len(), 2);
+        assert_eq!(status.monitors[0].name, "DP-1");
+        assert_eq!(status.monitors[1].name, "DP-2");
+        assert_eq!(status.monitors[1].idle_secs, 7);
+        assert_eq!(status.monitors[1].timeout_secs, 30);
+        assert!(!status.monitors[1].active);
+    }
+
+    /// Verify a MonitorRemoved event translates into a RendererCommand
+    #[test]
+    fn event_monitor_removed() {
+        let mut tracker = IdleTracker::new(Config::default());
+        let cmds = tracker.handle_event(
+            Instant::now(),
+            &HyprEvent::MonitorRemoved("DP-1".to_string()),
+        );
+        assert_eq!(
+            cmds,
+            vec![RendererCommand::MonitorRemoved {
+                monitor: "DP-1".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn away_message_is_forwarded_and_reported() {
+        let mut tracker = IdleTracker::new(Config::default());
+        let t0 = Instant::now();
+        let set = ControlRequest::Message {
+            text: Some("Back at 14:30".into()),
+        };
+        let (response, cmds) = tracker.handle_control(t0, &set);
+        assert_eq!(response, ControlResponse::Ok);
+        assert_eq!(
+            cmds,
+            vec![RendererCommand::SetMessage {
+                text: Some("Back at 14:30".into())
+            }]
+        );
+        let (ControlResponse::Status(status), _) = tracker.handle_control(t0, &ControlRequest::Status) else {
+            panic!("Expected Status");
+        };
+        assert_eq!(status.message.as_deref(), Some("Back at 14:30"));
+
+        // A bad message leaves the old one showing
+        let bad = ControlRequest::Message {
+            text: Some("a\0b".into()),
+        };
+        let (response, cmds) = tracker.handle_control(t0, &bad);
+        assert!(matches!(response, ControlResponse::Error { .. }));
+        assert!(cmds.is_empty());
+
+        let (response, cmds) = tracker.handle_control(t0, &ControlRequest::Message { text: None });
+        assert_eq!(response, ControlResponse::Ok);
+        assert_eq!(cmds, vec![RendererCommand::SetMessage { text: None }]);
+        assert_eq!(tracker.status(t0).message, None);
+    }
+
+    #[test]
+    fn reload_restarts_only_changed_monitors() {
+        let cfg = config("[general]\nidle_timeout = 10\n\n[screensaver]\nname = \"matrix\"\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = vec![monitor("DP-1", 0), monitor("DP-2", 1920), monitor("DP-3", 3840)];
+        let t0 = Instant::now();
+        let at = |s| t0 + Duration::from_secs(s);
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        let cmds = tracker.poll(at(10), ON_DP1, &monitors, false);
+        assert_eq!(cmds, vec![start("DP-2", "matrix"), start("DP-3", "matrix")]);
+
+        // DP-2 gets its own saver, DP-3 keeps the global one, DP-1 is idle
+        // but not showing anything, so nothing to restart there
+        let cmds = tracker.apply_config(config(
+            "[general]\nidle_timeout = 10\n\n[screensaver]\nname = \"matrix\"\n\n\
+             [monitors.DP-1]\nscreensaver = \"plasmula\"\n\n\
+             [monitors.DP-2]\nscreensaver = \"starfield\"\n",
+        ));
+        assert_eq!(cmds, vec![stop("DP-2"), start("DP-2", "starfield")]);
+        assert!(tracker.is_active("DP-2"));
+        assert!(tracker.is_active("DP-3"));
+
+        // A per-monitor fps override restarts just that monitor, and the
+        // merged settings travel in the Start command
+        let cmds = tracker.apply_config(config(
+            "[general]\nidle_timeout = 10\n\n[screensaver]\nname = \"matrix\"\n\n\
+             [monitors.DP-2]\nscreensaver = \"starfield\"\n\n\
+             [monitors.DP-3]\nfps = 60\n",
+        ));
+        assert_eq!(
+            cmds,
+            vec![
+                stop("DP-3"),
+                RendererCommand::Start {
+                    monitor: "DP-3".into(),
+                    screensaver: ScreensaverConfig {
+                        name: "matrix".into(),
+                        fps: 60,
+                        ..Default::default()
+                    },
+                },
+            ]
+        );
+
+        // Changing a global setting restarts every monitor that inherits it
+        let cmds = tracker.apply_config(config(
+            "[general]\nidle_timeout = 10\n\n[screensaver]\nname = \"matrix\"\nopacity = 0.5\n\n\
+             [monitors.DP-2]\nscreensaver = \"starfield\"\nopacity = 1.0\n\n\
+             [monitors.DP-3]\nfps = 60\n",
+        ));
+        let restarted: Vec<&str> = cmds
+            .iter()
+            .filter_map(|cmd| match cmd {
+                RendererCommand::Start { monitor, .. } => Some(monitor.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(restarted, vec!["DP-3"]);
+    }
+
+    #[test]
+    fn reload_stops_disabled_and_updates_session_idle() {
+        let mut tracker = IdleTracker::new(config("[general]\nidle_timeout = 10\n"));
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        tracker.poll(t0 + Duration::from_secs(10), ON_DP1, &monitors, false);
+        assert!(tracker.is_active("DP-2"));
+
+        let cmds = tracker.apply_config(config(
+            "[general]\nidle_timeout = 10\nsession_idle = false\n\n[monitors.DP-2]\ndisabled = true\n",
+        ));
+        assert_eq!(
+            cmds,
+            vec![
+                RendererCommand::SetSessionIdle {
+                    enabled: false,
+                    timeout_secs: 10,
+                },
+                stop("DP-2"),
+            ]
+        );
+        assert!(!tracker.is_active("DP-2"));
+
+        // Disabled monitors stay off afterwards
+        let cmds = tracker.poll(t0 + Duration::from_secs(30), ON_DP1, &monitors, false);
+        assert!(cmds.is_empty());
+
+        // Reloading an identical config is a no-op
+        let cmds = tracker.apply_config(config(
+            "[general]\nidle_timeout = 10\nsession_idle = false\n\n[monitors.DP-2]\ndisabled = true\n",
+        ));
+        assert!(cmds.is_empty());
+    }
+
+    /// Verify a FocusedMonitor event translates into a Stop command and
+    /// clears the tracker's view of that monitor
+    #[test]
+    fn event_focus_sends_stop() {
+        let cfg = config("[general]\nidle_timeout = 10\n");
+        let mut tracker = IdleTracker::new(cfg);
+        let monitors = two_monitors();
+        let t0 = Instant::now();
+
+        tracker.poll(t0, ON_DP1, &monitors, false);
+        tracker.poll(t0 + Duration::from_secs(10), ON_DP1, &monitors, false);
+        assert!(tracker.is_active("DP-2"));
+
+        let cmds = tracker.handle_event(
+            t0 + Duration::from_secs(11),
+            &HyprEvent::FocusedMonitor("DP-2".to_string()),
+        );
+        assert_eq!(cmds, vec![stop("DP-2")]);
+        assert!(!tracker.is_active("DP-2"));
+
+        // DP-2's timer was reset by the focus change
+        let cmds = tracker.poll(t0 + Duration::from_secs(12), ON_DP1_MOVED, &monitors, false);
+        assert!(cmds.is_empty());
+    }
+
+    /// Verify workspace and unknown events don't produce renderer commands
+    #[test]
+    fn event_ignores_workspace() {
+        let mut tracker = IdleTracker::new(Config::default());
+        let now = Instant::now();
+        for event in [
+            HyprEvent::Workspace("3".to_string()),
+            HyprEvent::Other("openwindow>>data".to_string()),
+            HyprEvent::MonitorAdded("DP-2".to_string()),
+        ] {
+            assert!(tracker.handle_event(now, &event).is_empty());
+        }
     }
 }
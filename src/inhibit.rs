@@ -0,0 +1,319 @@
+//! Screensaver inhibitors ("caffeinate")
+//!
+//! An inhibitor suppresses automatic screensaver starts, either globally or
+//! for one monitor, until it is removed, its duration runs out, or the process
+//! that owns it exits. The idle tracker consults the registry before emitting
+//! any automatic `RendererCommand::Start`.
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// What a client asks for when adding an inhibitor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InhibitSpec {
+    /// Only inhibit this monitor (None = all monitors)
+    #[serde(default)]
+    pub monitor: Option<String>,
+    /// Expire after this many seconds (None = until removed)
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    /// Free-form reason shown in status output
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Expire when this process exits
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+/// An active inhibitor as reported by `hyprfresh ctl status`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InhibitorStatus {
+    pub id: u64,
+    pub monitor: Option<String>,
+    pub reason: Option<String>,
+    /// Seconds until expiry (None = no time limit)
+    pub remaining_secs: Option<u64>,
+    pub pid: Option<u32>,
+}
+
+#[derive(Debug)]
+struct Inhibitor {
+    id: u64,
+    spec: InhibitSpec,
+    expires: Option<Instant>,
+}
+
+/// Set of active inhibitors
+#[derive(Debug, Default)]
+pub struct InhibitorRegistry {
+    next_id: u64,
+    inhibitors: Vec<Inhibitor>,
+}
+
+impl InhibitorRegistry {
+    /// Register an inhibitor created at `now` and return its id. Fails if
+    /// the duration is zero or reaches past what the clock can represent.
+    pub fn add(&mut self, now: Instant, spec: InhibitSpec) -> Result<u64, String> {
+        let expires = match spec.duration_secs {
+            Some(0) => return Err("duration must be at least one second".to_string()),
+            Some(secs) => Some(
+                now.checked_add(Duration::from_secs(secs))
+                    .ok_or_else(|| format!("duration of {} seconds is out of range", secs))?,
+            ),
+            None => None,
+        };
+        self.next_id += 1;
+        let id = self.next_id;
+        info!(
+            "Inhibitor {} added ({}{}{})",
+            id,
+            spec.monitor.as_deref().unwrap_or("all monitors"),
+            spec.duration_secs
+                .map(|s| format!(", for {}", format_duration(s)))
+                .unwrap_or_default(),
+            spec.reason
+                .as_deref()
+                .map(|r| format!(", reason: {}", r))
+                .unwrap_or_default(),
+        );
+        self.inhibitors.push(Inhibitor { id, spec, expires });
+        Ok(id)
+    }
+
+    /// Remove inhibitors by id, by monitor, or all of them when both are None.
+    /// Returns how many were removed.
+    pub fn remove(&mut self, id: Option<u64>, monitor: Option<&str>) -> usize {
+        let before = self.inhibitors.len();
+        self.inhibitors.retain(|inh| {
+            let matches = match (id, monitor) {
+                (Some(id), _) => inh.id == id,
+                (None, Some(monitor)) => inh.spec.monitor.as_deref() == Some(monitor),
+                (None, None) => true,
+            };
+            if matches {
+                info!("Inhibitor {} removed", inh.id);
+            }
+            !matches
+        });
+        before - self.inhibitors.len()
+    }
+
+    /// Drop inhibitors whose duration ran out by `now`
+    pub fn expire(&mut self, now: Instant) {
+        self.inhibitors.retain(|inh| {
+            let live = inh.expires.is_none_or(|t| now < t);
+            if !live {
+                info!("Inhibitor {} expired", inh.id);
+            }
+            live
+        });
+    }
+
+    /// Drop inhibitors whose owning process is no longer alive
+    pub fn prune_dead(&mut self, is_alive: impl Fn(u32) -> bool) {
+        self.inhibitors.retain(|inh| {
+            let live = inh.spec.pid.is_none_or(&is_alive);
+            if !live {
+                info!("Inhibitor {} released: owning process exited", inh.id);
+            }
+            live
+        });
+    }
+
+    /// Whether automatic starts on `monitor` are currently suppressed
+    pub fn blocks(&self, monitor: &str) -> bool {
+        self.inhibitors
+            .iter()
+            .any(|inh| inh.spec.monitor.as_deref().is_none_or(|m| m == monitor))
+    }
+
+    /// Whether any inhibitor covers all monitors
+    pub fn blocks_all(&self) -> bool {
+        self.inhibitors.iter().any(|inh| inh.spec.monitor.is_none())
+    }
+
+    /// Snapshot for status output
+    pub fn list(&self, now: Instant) -> Vec<InhibitorStatus> {
+        self.inhibitors
+            .iter()
+            .map(|inh| InhibitorStatus {
+                id: inh.id,
+                monitor: inh.spec.monitor.clone(),
+                reason: inh.spec.reason.clone(),
+                remaining_secs: inh
+                    .expires
+                    .map(|t| t.saturating_duration_since(now).as_secs()),
+                pid: inh.spec.pid,
+            })
+            .collect()
+    }
+}
+
+/// Whether a process with this pid exists
+pub fn process_alive(pid: u32) -> bool {
+    std::path::Path::new(&format!("/proc/{}", pid)).exists()
+}
+
+/// Parse a human duration like `90m`, `1h30m`, `45s` or a bare number of seconds
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let invalid = || format!("invalid duration '{}': expected e.g. 90m, 1h30m or 45s", input);
+    if let Ok(secs) = input.parse::<u64>() {
+        if secs == 0 {
+            return Err(invalid());
+        }
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return Err(format!("invalid duration '{}': unknown unit '{}'", input, c)),
+        };
+        let value: u64 = digits
+            .parse()
+            .map_err(|_| format!("invalid duration '{}': expected a number before '{}'", input, c))?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Format seconds compactly, e.g. `1h29m`, `4m10s`, `12s`
+pub fn format_duration(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    match (h, m, s) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}m", m),
+        (0, m, s) => format!("{}m{}s", m, s),
+        (h, 0, _) => format!("{}h", h),
+        (h, m, _) => format!("{}h{}m", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(monitor: Option<&str>, duration_secs: Option<u64>) -> InhibitSpec {
+        InhibitSpec {
+            monitor: monitor.map(str::to_string),
+            duration_secs,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_durations() {
+        let cases = [
+            ("90", 90),
+            ("45s", 45),
+            ("90m", 5400),
+            ("1h30m", 5400),
+            ("2h", 7200),
+            ("1d", 86400),
+        ];
+        for (input, secs) in cases {
+            assert_eq!(parse_duration(input), Ok(Duration::from_secs(secs)), "{}", input);
+        }
+        let overflow = ["999999999999999999d", "18446744073709551615s1s"];
+        for bad in ["", "m", "10x", "1h30", "0", "0s", "0m", "0h0m"].into_iter().chain(overflow) {
+            assert!(parse_duration(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(12), "12s");
+        assert_eq!(format_duration(240), "4m");
+        assert_eq!(format_duration(250), "4m10s");
+        assert_eq!(format_duration(3600), "1h");
+        assert_eq!(format_duration(5340), "1h29m");
+    }
+
+    #[test]
+    fn timed_inhibitor_expires() {
+        let mut reg = InhibitorRegistry::default();
+        let t0 = Instant::now();
+        reg.add(t0, spec(None, Some(60))).unwrap();
+        assert!(reg.blocks("DP-1"));
+        assert!(reg.blocks_all());
+        assert_eq!(reg.list(t0 + Duration::from_secs(15))[0].remaining_secs, Some(45));
+
+        reg.expire(t0 + Duration::from_secs(59));
+        assert!(reg.blocks("DP-1"));
+        reg.expire(t0 + Duration::from_secs(60));
+        assert!(!reg.blocks("DP-1"));
+    }
+
+    #[test]
+    fn zero_or_out_of_range_duration_is_rejected() {
+        let mut reg = InhibitorRegistry::default();
+        assert!(reg.add(Instant::now(), spec(None, Some(0))).is_err());
+        assert!(reg.add(Instant::now(), spec(None, Some(u64::MAX))).is_err());
+        assert!(reg.list(Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn monitor_inhibitor_is_scoped() {
+        let mut reg = InhibitorRegistry::default();
+        reg.add(Instant::now(), spec(Some("DP-2"), None)).unwrap();
+        assert!(reg.blocks("DP-2"));
+        assert!(!reg.blocks("DP-1"));
+        assert!(!reg.blocks_all());
+    }
+
+    #[test]
+    fn remove_by_id_monitor_or_all() {
+        let mut reg = InhibitorRegistry::default();
+        let now = Instant::now();
+        let a = reg.add(now, spec(None, None)).unwrap();
+        reg.add(now, spec(Some("DP-1"), None)).unwrap();
+        reg.add(now, spec(Some("DP-2"), None)).unwrap();
+
+        assert_eq!(reg.remove(Some(a), None), 1);
+        assert_eq!(reg.remove(Some(a), None), 0);
+        assert_eq!(reg.remove(None, Some("DP-1")), 1);
+        assert_eq!(reg.remove(None, None), 1);
+        assert!(reg.list(now).is_empty());
+    }
+
+    #[test]
+    fn owner_exit_releases_inhibitor() {
+        let mut reg = InhibitorRegistry::default();
+        reg.add(
+            Instant::now(),
+            InhibitSpec {
+                pid: Some(4242),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        reg.prune_dead(|pid| pid == 4242);
+        assert!(reg.blocks("DP-1"));
+        reg.prune_dead(|_| false);
+        assert!(!reg.blocks("DP-1"));
+    }
+
+    #[test]
+    fn own_process_is_alive() {
+        assert!(process_alive(std::process::id()));
+    }
+}
@@ -1,14 +1,23 @@
+mod clock;
 mod config;
+mod control;
 mod idle;
+mod inhibit;
 mod ipc;
+mod message;
+mod reload;
 mod renderer;
 mod screensavers;
+mod shader_watch;
+mod text;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use control::{ControlRequest, ControlResponse};
 use log::{error, info, warn};
-use renderer::{RendererCommand, SessionIdleConfig, WaylandState};
+use renderer::{RendererCommand, RendererEvent, SessionIdleConfig, WaylandState};
 use smithay_client_toolkit::reexports::calloop::{self, EventLoop};
 use smithay_client_toolkit::reexports::calloop_wayland_source::WaylandSource;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -40,51 +49,204 @@ struct Cli {
     /// List available screensavers
     #[arg(long)]
     list: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long, requires = "list")]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Control a running daemon over its local socket
+    Ctl {
+        /// Print the raw JSON response
+        #[arg(long)]
+        json: bool,
+
+        #[command(subcommand)]
+        action: CtlAction,
+    },
+    /// Validate the config file and exit non-zero if it has errors
+    CheckConfig {
+        /// Config file to check (default: the --config path)
+        path: Option<String>,
+        /// Skip comparing [monitors.*] names against connected outputs
+        #[arg(long)]
+        offline: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CtlAction {
+    /// Show per-monitor idle and screensaver state
+    Status,
+    /// Start a screensaver now, regardless of idle state
+    Start {
+        /// Monitor name, or "all" (default: all)
+        monitor: Option<String>,
+        /// Screensaver to run (default: the configured one)
+        screensaver: Option<String>,
+    },
+    /// Stop a running screensaver
+    Stop {
+        /// Monitor name, or "all" (default: all)
+        monitor: Option<String>,
+    },
+    /// Stop screensavers from starting automatically
+    Inhibit {
+        /// Only inhibit this monitor (default: all)
+        monitor: Option<String>,
+        /// Expire after a duration, e.g. 90m, 1h30m, 45s (default: until uninhibited)
+        #[arg(long = "for", value_name = "DURATION", value_parser = inhibit::parse_duration)]
+        duration: Option<std::time::Duration>,
+        /// Reason shown in `ctl status`
+        #[arg(long)]
+        reason: Option<String>,
+        /// Expire when this process exits
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Allow screensavers to start automatically again
+    Uninhibit {
+        /// Only remove inhibitors for this monitor (default: all inhibitors)
+        monitor: Option<String>,
+        /// Remove a single inhibitor by id (see `ctl status`)
+        #[arg(long, conflicts_with = "monitor")]
+        id: Option<u64>,
+    },
+    /// Re-read the config file
+    Reload,
+    /// Set the text of the `message` screensaver, until cleared
+    Message {
+        /// Lines of the message
+        #[arg(required_unless_present = "clear")]
+        lines: Vec<String>,
+        /// Go back to the message from the config
+        #[arg(long, conflicts_with = "lines")]
+        clear: bool,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
 
     // Initialize logging
-    let log_level = if cli.verbose { "debug" } else { "info" };
+    // naga logs every shader it rejects at error level; we report those
+    // failures ourselves with file and line
+    let log_level = if cli.verbose { "debug,naga=off" } else { "info,naga=off" };
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
 
+    // Control client: talk to the running daemon and exit
+    match cli.command {
+        Some(Command::Ctl { json, action }) => std::process::exit(run_ctl(action, json)),
+        Some(Command::CheckConfig { path, offline }) => {
+            let path = shellexpand(path.as_deref().unwrap_or(&cli.config));
+            std::process::exit(run_check_config(&path, !offline));
+        }
+        None => {}
+    }
+
     info!("HyprFresh v{} starting", env!("CARGO_PKG_VERSION"));
 
-    // List screensavers and exit
+    let config_path = shellexpand(&cli.config);
+
+    // List screensavers (and the config's presets) and exit
     if cli.list {
-        screensavers::list_available();
+        let presets = config::Config::load(&config_path)
+            .map(|c| c.presets)
+            .unwrap_or_default();
+        let mut presets: Vec<(String, String)> = presets
+            .iter()
+            .map(|(name, preset)| (name.clone(), preset.describe()))
+            .collect();
+        presets.sort();
+        screensavers::list_available(&presets, cli.json);
         return;
     }
 
     // Load config
-    let config_path = shellexpand(&cli.config);
-    let cfg = match config::Config::load(&config_path) {
-        Ok(c) => c,
-        Err(e) => {
-            error!("Failed to load config from {}: {}", config_path, e);
-            info!("Using default configuration");
-            config::Config::default()
+    let cfg = if std::path::Path::new(&config_path).exists() {
+        match config::Config::load(&config_path) {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Invalid config {}:\n{}", config_path, e);
+                error!("Fix it (see `hyprfresh check-config`) or move it away to use defaults");
+                std::process::exit(1);
+            }
         }
+    } else {
+        info!("No config at {}, using default configuration", config_path);
+        config::Config::default()
     };
 
     // Preview mode: run a screensaver immediately
     if let Some(ref name) = cli.preview {
-        if !screensavers::is_valid(name) {
-            error!("Unknown screensaver '{}'. Use --list to see available options.", name);
+        if let Err(e) = cfg.resolve_name(name) {
+            error!("{}. Use --list to see available options.", e);
             std::process::exit(1);
         }
         info!("Preview mode: running screensaver '{}'", name);
-        run_preview(name, cli.monitor.as_deref(), cli.duration);
+        run_preview(&cfg, name, cli.monitor.as_deref(), cli.duration);
         return;
     }
 
     // Daemon mode
-    run_daemon(cfg);
+    run_daemon(cfg, config_path);
+}
+
+/// Check a config file, print the problems found and return the exit code
+fn run_check_config(path: &str, check_outputs: bool) -> i32 {
+    let source = match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{}: error: {}", path, e);
+            return 1;
+        }
+    };
+
+    let (cfg, mut diagnostics) = config::Config::check_source(&source);
+
+    if let Some(ref cfg) = cfg
+        && check_outputs
+        && !cfg.monitors.is_empty()
+    {
+        let monitors = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.into())
+            .and_then(|rt| rt.block_on(ipc::get_monitors()));
+        match monitors {
+            Ok(monitors) => {
+                let names: Vec<String> = monitors.into_iter().map(|m| m.name).collect();
+                diagnostics.extend(cfg.check_outputs(&source, &names));
+            }
+            Err(e) => eprintln!("note: monitor names not checked (Hyprland unavailable: {})", e),
+        }
+    }
+
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic.render(path));
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == config::Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    if errors > 0 {
+        println!("{}: {} error(s), {} warning(s)", path, errors, warnings);
+        1
+    } else {
+        println!("{}: OK ({} warning(s))", path, warnings);
+        0
+    }
 }
 
 /// Run the main daemon: Wayland renderer on main thread, tokio idle loop on background thread
-fn run_daemon(cfg: config::Config) {
+fn run_daemon(cfg: config::Config, config_path: String) {
     let session_idle_active = Arc::new(AtomicBool::new(false));
     let session_idle_config = SessionIdleConfig {
         enabled: cfg.general.session_idle,
@@ -111,6 +273,11 @@ fn run_daemon(cfg: config::Config) {
     };
 
     let loop_handle = event_loop.handle();
+    state.set_loop_handle(loop_handle.clone());
+
+    // Channel: renderer lifecycle events -> idle loop
+    let (status_tx, status_rx) = mpsc::unbounded_channel::<RendererEvent>();
+    state.set_event_sender(status_tx);
 
     // Insert Wayland event source into calloop
     WaylandSource::new(conn, event_queue)
@@ -139,6 +306,21 @@ fn run_daemon(cfg: config::Config) {
         warn!("Failed to set signal handler: {}", e);
     }
 
+    // Bind the control socket before spawning anything, so a second daemon
+    // can't take it over from a running one
+    let control_socket = control::socket_path().and_then(|path| {
+        let listener = control::bind(&path)?;
+        info!("Control socket at {}", path.display());
+        Ok((path, listener))
+    });
+    let (control_path, control_listener) = match control_socket {
+        Ok((path, listener)) => (Some(path), Some(listener)),
+        Err(e) => {
+            warn!("Control socket unavailable: {}", e);
+            (None, None)
+        }
+    };
+
     // Spawn the tokio runtime on a background thread for the idle loop + IPC
     let idle_config = cfg.clone();
     std::thread::spawn(move || {
@@ -148,26 +330,44 @@ fn run_daemon(cfg: config::Config) {
             .expect("failed to create tokio runtime");
 
         rt.block_on(async move {
-            // Channel: Hyprland events -> event bridge
+            // Channel: Hyprland events -> idle loop
             let (event_tx, event_rx) = mpsc::channel(64);
 
-            let calloop_tx_idle = calloop_tx.clone();
-            let calloop_tx_events = calloop_tx;
+            // Channel: control socket -> idle loop
+            let (control_tx, control_rx) = mpsc::channel(16);
+
+            // Config hot-reload: file changes and SIGHUP both request a reload
+            tokio::spawn(reload::watch_config(
+                PathBuf::from(&config_path),
+                control_tx.clone(),
+            ));
+            tokio::spawn(reload::watch_sighup(control_tx.clone()));
+
+            if let Some(listener) = control_listener {
+                tokio::spawn(control::serve(listener, control_tx));
+            }
+
+            let inputs = idle::IdleInputs {
+                hypr_events: event_rx,
+                renderer_events: status_rx,
+                control: control_rx,
+                session_idle_active,
+            };
 
-            // Spawn idle poll loop with a bridge to calloop
+            // Spawn idle loop with a bridge to calloop
             let idle_handle = tokio::spawn(async move {
                 let (tx, mut rx) = mpsc::channel::<RendererCommand>(32);
 
                 // Forward tokio mpsc -> calloop channel
                 let fwd = tokio::spawn(async move {
                     while let Some(cmd) = rx.recv().await {
-                        if calloop_tx_idle.send(cmd).is_err() {
+                        if calloop_tx.send(cmd).is_err() {
                             break;
                         }
                     }
                 });
 
-                if let Err(e) = idle::run_idle_loop(idle_config, tx, session_idle_active).await {
+                if let Err(e) = idle::run_idle_loop(idle_config, config_path, tx, inputs).await {
                     error!("Idle loop exited with error: {}", e);
                 }
 
@@ -181,23 +381,7 @@ fn run_daemon(cfg: config::Config) {
                 }
             });
 
-            // Spawn event bridge (HyprEvents -> RendererCommands -> calloop)
-            let bridge_handle = tokio::spawn(async move {
-                let (tx, mut rx) = mpsc::channel::<RendererCommand>(32);
-
-                let fwd = tokio::spawn(async move {
-                    while let Some(cmd) = rx.recv().await {
-                        if calloop_tx_events.send(cmd).is_err() {
-                            break;
-                        }
-                    }
-                });
-
-                idle::run_event_bridge(event_rx, tx).await;
-                fwd.abort();
-            });
-
-            let _ = tokio::join!(idle_handle, event_handle, bridge_handle);
+            let _ = tokio::join!(idle_handle, event_handle);
         });
     });
 
@@ -219,11 +403,130 @@ fn run_daemon(cfg: config::Config) {
         }
     }
 
+    if let Some(path) = control_path {
+        control::cleanup(&path);
+    }
+
     info!("HyprFresh shutting down");
 }
 
+/// Send a control request to the running daemon. Returns the exit code.
+fn run_ctl(action: CtlAction, json: bool) -> i32 {
+    // "all" (or nothing) targets every monitor
+    let target = |monitor: Option<String>| monitor.filter(|m| m != "all");
+
+    let request = match action {
+        CtlAction::Status => ControlRequest::Status,
+        CtlAction::Start {
+            monitor,
+            screensaver,
+        } => {
+            // Screensaver names (which may be presets) are checked by the daemon
+            ControlRequest::Start {
+                monitor: target(monitor),
+                screensaver,
+            }
+        }
+        CtlAction::Stop { monitor } => ControlRequest::Stop {
+            monitor: target(monitor),
+        },
+        CtlAction::Inhibit {
+            monitor,
+            duration,
+            reason,
+            pid,
+        } => ControlRequest::Inhibit(inhibit::InhibitSpec {
+            monitor: target(monitor),
+            duration_secs: duration.map(|d| d.as_secs()),
+            reason,
+            pid,
+        }),
+        CtlAction::Uninhibit { monitor, id } => ControlRequest::Uninhibit {
+            id,
+            monitor: target(monitor),
+        },
+        CtlAction::Reload => ControlRequest::Reload,
+        CtlAction::Message { lines, clear } => ControlRequest::Message {
+            text: (!clear).then(|| lines.join("\n")),
+        },
+    };
+
+    let response = match control::socket_path().and_then(|path| control::send_request(&path, &request)) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&response).unwrap_or_default());
+    } else {
+        match response {
+            ControlResponse::Ok => {}
+            ControlResponse::Inhibited { id } => println!("Inhibitor {} added", id),
+            ControlResponse::Status(ref status) => {
+                println!("Session idle: {}", if status.session_idle { "yes" } else { "no" });
+                println!("Inhibited:    {}", if status.inhibited { "yes" } else { "no" });
+                if let Some(ref message) = status.message {
+                    println!("Message:      {}", message.replace('\n', " / "));
+                }
+                println!();
+                for m in &status.monitors {
+                    let state = if m.active {
+                        "active"
+                    } else if m.disabled {
+                        "disabled"
+                    } else if m.inhibited {
+                        "inhibited"
+                    } else {
+                        "idle"
+                    };
+                    println!(
+                        "  {:<12} {:<9} {:<16} idle {}s / {}s",
+                        m.name, state, m.screensaver, m.idle_secs, m.timeout_secs
+                    );
+                    if let Some(ref error) = m.error {
+                        println!("  {:<12} showing blank: {}", "", error);
+                    }
+                }
+                if !status.inhibitors.is_empty() {
+                    println!();
+                    println!("Inhibitors:");
+                    for inh in &status.inhibitors {
+                        let remaining = inh
+                            .remaining_secs
+                            .map(|s| format!("{} left", inhibit::format_duration(s)))
+                            .unwrap_or_else(|| "until removed".to_string());
+                        let owner = inh.pid.map(|p| format!(", while pid {} runs", p)).unwrap_or_default();
+                        println!(
+                            "  #{:<3} {:<12} {}{}{}",
+                            inh.id,
+                            inh.monitor.as_deref().unwrap_or("all"),
+                            remaining,
+                            owner,
+                            inh.reason.as_deref().map(|r| format!(" — {}", r)).unwrap_or_default(),
+                        );
+                    }
+                }
+            }
+            ControlResponse::Error { ref message } => eprintln!("Error: {}", message),
+        }
+    }
+
+    match response {
+        ControlResponse::Error { .. } => 1,
+        _ => 0,
+    }
+}
+
 /// Run a screensaver in preview mode (immediate, no idle detection)
-fn run_preview(screensaver_name: &str, monitor_filter: Option<&str>, duration: Option<u64>) {
+fn run_preview(
+    cfg: &config::Config,
+    screensaver_name: &str,
+    monitor_filter: Option<&str>,
+    duration: Option<u64>,
+) {
     // Preview mode doesn't use session-wide idle
     let session_idle_config = SessionIdleConfig {
         enabled: false,
@@ -241,6 +544,7 @@ fn run_preview(screensaver_name: &str, monitor_filter: Option<&str>, duration: O
 
     let mut event_loop: EventLoop<WaylandState> =
         EventLoop::try_new().expect("failed to create event loop");
+    state.set_loop_handle(event_loop.handle());
 
     WaylandSource::new(conn, event_queue)
         .insert(event_loop.handle())
@@ -285,15 +589,26 @@ fn run_preview(screensaver_name: &str, monitor_filter: Option<&str>, duration: O
         all_names
     };
 
-    // Start screensaver on target outputs
+    // Show the away message the daemon would
+    if let Some(text) = message::state_path().as_deref().and_then(message::load) {
+        state.queue_command(RendererCommand::SetMessage { text: Some(text) });
+    }
+
+    // Start screensaver on target outputs, with each monitor's settings
     for name in &targets {
         state.queue_command(RendererCommand::Start {
             monitor: name.clone(),
-            screensaver: screensaver_name.to_string(),
+            screensaver: cfg.screensaver_named(name, screensaver_name),
         });
     }
     state.process_commands();
 
+    // Rebuild the shader in place whenever its file is saved
+    let saver = cfg.screensaver_named(&targets[0], screensaver_name).name;
+    if let Err(e) = shader_watch::watch(&event_loop.handle(), &saver) {
+        warn!("Shader hot-reload disabled: {}", e);
+    }
+
     let target_desc = targets.join(", ");
     match duration {
         Some(secs) => info!(
@@ -0,0 +1,98 @@
+//! Away message
+//!
+//! `hyprfresh ctl message` sets the text of the `message` screensaver while
+//! the daemon runs. It takes the place of the `message` option from the
+//! config until it's cleared, and is kept in a state file
+//! (`$XDG_STATE_HOME/hyprfresh/message`) so it survives restarts.
+
+use log::warn;
+use std::path::{Path, PathBuf};
+
+/// Screensaver option the live message stands in for
+pub const OPTION: &str = "message";
+
+/// Longest message accepted, in bytes
+pub const MAX_LEN: usize = 4096;
+
+/// Path of the state file
+pub fn state_path() -> Option<PathBuf> {
+    let dir = match std::env::var("XDG_STATE_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var("HOME").ok()?).join(".local/state"),
+    };
+    Some(dir.join("hyprfresh/message"))
+}
+
+/// Check a message before showing it
+pub fn check(text: &str) -> Result<(), String> {
+    if text.len() > MAX_LEN {
+        return Err(format!("message is {} bytes, the limit is {}", text.len(), MAX_LEN));
+    }
+    if text.contains('\0') {
+        return Err("message can't contain NUL characters".to_string());
+    }
+    Ok(())
+}
+
+/// The saved message, if there is one
+pub fn load(path: &Path) -> Option<String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => match check(&text) {
+            Ok(()) => Some(text),
+            Err(e) => {
+                warn!("Ignoring saved message in {}: {}", path.display(), e);
+                None
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            warn!("Failed to read saved message {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Save a message, or forget the saved one. The file is replaced in one
+/// step, so a crash never leaves half a message behind.
+pub fn save(path: &Path, text: Option<&str>) -> Result<(), String> {
+    let fail = |e: std::io::Error| format!("failed to save the message to {}: {}", path.display(), e);
+    let Some(text) = text else {
+        return match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(fail(e)),
+            _ => Ok(()),
+        };
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(fail)?;
+    }
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, text).map_err(fail)?;
+    std::fs::rename(&temp, path).map_err(fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_loads_and_clears() {
+        let dir = std::env::temp_dir().join(format!("hyprfresh_test_message_{}", std::process::id()));
+        let path = dir.join("hyprfresh/message");
+        assert_eq!(load(&path), None);
+
+        save(&path, Some("Back at 14:30\nping me on chat")).unwrap();
+        assert_eq!(load(&path).as_deref(), Some("Back at 14:30\nping me on chat"));
+        save(&path, Some("")).unwrap();
+        assert_eq!(load(&path).as_deref(), Some(""));
+
+        save(&path, None).unwrap();
+        assert_eq!(load(&path), None);
+        // Clearing twice is fine
+        save(&path, None).unwrap();
+
+        assert!(check(&"x".repeat(MAX_LEN)).is_ok());
+        assert!(check(&"x".repeat(MAX_LEN + 1)).is_err());
+        assert!(check("a\0b").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
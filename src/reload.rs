@@ -0,0 +1,268 @@
+//! Configuration hot-reload
+//!
+//! The config file is watched with inotify (its parent directory, so editors
+//! that save by renaming a temp file are caught too), and SIGHUP triggers a
+//! reload as well. Both just send `ControlRequest::Reload` into the idle loop,
+//! the same path `hyprfresh ctl reload` takes.
+//!
+//! `ConfigReloader` does the actual re-read: it parses and validates the new
+//! file, logs a key-level diff against the last accepted version, and hands
+//! back the new `Config` only if it is valid.
+
+use crate::config::{self, Config, Severity};
+use crate::control::{self, ControlMessage, ControlRequest, ControlResponse};
+use log::{debug, info, warn};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use std::os::fd::{AsFd, AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use tokio::io::unix::AsyncFd;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc;
+use tokio::time::{self, Duration};
+
+/// Editors often write a file in several steps; wait this long after the
+/// first change before reloading so we see the final contents.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Re-reads the config file and tracks the last accepted version for diffs
+#[derive(Debug)]
+pub struct ConfigReloader {
+    path: String,
+    accepted: toml::Table,
+}
+
+impl ConfigReloader {
+    /// Start from whatever is currently on disk (empty if unreadable)
+    pub fn new(path: &str) -> Self {
+        let accepted = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| s.parse::<toml::Table>().ok())
+            .unwrap_or_default();
+        Self {
+            path: path.to_string(),
+            accepted,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Load, validate and accept the file's current contents.
+    ///
+    /// On error the previously accepted version stays current and the
+    /// message includes the changes that were rejected.
+    pub fn reload(&mut self) -> Result<Config, String> {
+        let content = std::fs::read_to_string(&self.path)
+            .map_err(|e| format!("failed to read {}: {}", self.path, e))?;
+        let table: toml::Table = content
+            .parse()
+            .map_err(|e| format!("failed to parse {}: {}", self.path, e))?;
+
+        let diff = diff_tables(&self.accepted, &table);
+        let describe = |diff: &[String]| {
+            if diff.is_empty() {
+                "no changes".to_string()
+            } else {
+                diff.join(", ")
+            }
+        };
+
+        let (config, diagnostics) = Config::check_source(&content);
+        let config = match config {
+            Some(config) if !diagnostics.iter().any(|d| d.severity == Severity::Error) => config,
+            _ => {
+                return Err(format!(
+                    "{} (rejected changes: {})",
+                    config::render_errors(&self.path, &diagnostics).replace('\n', "; "),
+                    describe(&diff)
+                ));
+            }
+        };
+
+        info!("Config changes: {}", describe(&diff));
+        self.accepted = table;
+        Ok(config)
+    }
+}
+
+/// Describe key-level differences between two TOML tables, e.g.
+/// `general.idle_timeout: 300 -> 60`, `+monitors.DP-1.screensaver = "starfield"`.
+pub fn diff_tables(old: &toml::Table, new: &toml::Table) -> Vec<String> {
+    let mut out = Vec::new();
+    diff_into("", old, new, &mut out);
+    out
+}
+
+fn diff_into(prefix: &str, old: &toml::Table, new: &toml::Table, out: &mut Vec<String>) {
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match (old.get(key), new.get(key)) {
+            (Some(toml::Value::Table(a)), Some(toml::Value::Table(b))) => {
+                diff_into(&path, a, b, out)
+            }
+            (Some(a), Some(b)) if a != b => out.push(format!("{}: {} -> {}", path, a, b)),
+            (Some(_), Some(_)) => {}
+            (Some(a), None) => out.push(format!("-{} = {}", path, a)),
+            (None, Some(b)) => out.push(format!("+{} = {}", path, b)),
+            (None, None) => unreachable!(),
+        }
+    }
+}
+
+/// `Inotify` only implements `AsFd`; tokio's `AsyncFd` wants `AsRawFd`
+struct InotifyFd(Inotify);
+
+impl AsRawFd for InotifyFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_fd().as_raw_fd()
+    }
+}
+
+/// Watch the config file and request a reload whenever it changes
+pub async fn watch_config(path: PathBuf, tx: mpsc::Sender<ControlMessage>) {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        warn!("Cannot watch config path {}", path.display());
+        return;
+    };
+
+    let inotify = match setup_watch(dir) {
+        Ok(fd) => fd,
+        Err(e) => {
+            warn!("Config hot-reload disabled: cannot watch {}: {}", dir.display(), e);
+            return;
+        }
+    };
+    info!("Watching {} for changes", path.display());
+
+    loop {
+        let mut guard = match inotify.readable().await {
+            Ok(g) => g,
+            Err(e) => {
+                warn!("Config watcher stopped: {}", e);
+                return;
+            }
+        };
+
+        let events = match inotify.get_ref().0.read_events() {
+            Ok(events) => events,
+            Err(nix::errno::Errno::EAGAIN) => {
+                guard.clear_ready();
+                continue;
+            }
+            Err(e) => {
+                warn!("Config watcher stopped: {}", e);
+                return;
+            }
+        };
+        drop(guard);
+
+        if !events.iter().any(|ev| ev.name.as_deref() == Some(file_name)) {
+            continue;
+        }
+
+        // Let the editor finish writing, then swallow the rest of the burst
+        time::sleep(DEBOUNCE).await;
+        let _ = inotify.get_ref().0.read_events();
+
+        debug!("Config file changed, reloading");
+        request_reload(&tx).await;
+    }
+}
+
+fn setup_watch(dir: &Path) -> std::io::Result<AsyncFd<InotifyFd>> {
+    let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?;
+    inotify.add_watch(
+        dir,
+        AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO | AddWatchFlags::IN_CREATE,
+    )?;
+    AsyncFd::new(InotifyFd(inotify))
+}
+
+/// Request a reload on every SIGHUP
+pub async fn watch_sighup(tx: mpsc::Sender<ControlMessage>) {
+    let mut hup = match signal(SignalKind::hangup()) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Failed to install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+
+    while hup.recv().await.is_some() {
+        info!("SIGHUP received, reloading config");
+        request_reload(&tx).await;
+    }
+}
+
+async fn request_reload(tx: &mpsc::Sender<ControlMessage>) {
+    // The idle loop logs the outcome; nothing else to do with it here
+    if let ControlResponse::Error { message } = control::dispatch(ControlRequest::Reload, tx).await {
+        debug!("Reload request failed: {}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(src: &str) -> toml::Table {
+        src.parse().unwrap()
+    }
+
+    #[test]
+    fn diff_reports_changed_added_removed() {
+        let old = table("[general]\nidle_timeout = 300\npoll_interval = 500\n[screensaver]\nname = \"matrix\"\n");
+        let new = table("[general]\nidle_timeout = 60\n[screensaver]\nname = \"matrix\"\n[monitors.DP-1]\nscreensaver = \"starfield\"\n");
+        assert_eq!(
+            diff_tables(&old, &new),
+            vec![
+                "general.idle_timeout: 300 -> 60".to_string(),
+                "-general.poll_interval = 500".to_string(),
+                "+monitors = { DP-1 = { screensaver = \"starfield\" } }".to_string(),
+            ]
+        );
+        assert!(diff_tables(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn reloader_keeps_last_good_config() {
+        let dir = std::env::temp_dir().join(format!("hyprfresh_test_reload_{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let path = dir.join("hyprfresh.toml");
+        let path_str = path.to_str().unwrap();
+
+        std::fs::write(&path, "[general]\nidle_timeout = 300\n").unwrap();
+        let mut reloader = ConfigReloader::new(path_str);
+
+        std::fs::write(&path, "[general]\nidle_timeout = 60\n").unwrap();
+        assert_eq!(reloader.reload().unwrap().general.idle_timeout, 60);
+
+        // Syntax error
+        std::fs::write(&path, "[general\nidle_timeout = 10\n").unwrap();
+        assert!(reloader.reload().is_err());
+
+        // Parses, but fails validation: the rejected diff is reported
+        std::fs::write(&path, "[general]\nidle_timeout = 60\npoll_interval = 0\n").unwrap();
+        let err = reloader.reload().unwrap_err();
+        assert!(err.contains("+general.poll_interval = 0"), "{}", err);
+
+        // The diff base is still the last accepted file
+        std::fs::write(&path, "[general]\nidle_timeout = 30\n").unwrap();
+        reloader.reload().unwrap();
+        assert_eq!(
+            diff_tables(&table("[general]\nidle_timeout = 30\n"), &reloader.accepted),
+            Vec::<String>::new()
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -6,8 +6,14 @@
 //! Architecture:
 //! - One layer surface per monitor that needs a screensaver
 //! - Surfaces are created at the overlay layer (above everything)
-//! - Each surface gets its own wgpu render pipeline
-//! - Screensaver modules provide WGSL fragment shaders
+//! - Each surface gets its own wgpu render pipeline, plus one per offscreen
+//!   pass with ping-pong textures sized to the output (see `passes`), and
+//!   uploads the images its screensaver's options name (see `images`)
+//! - Each surface runs a `Screensaver` instance that provides the WGSL
+//!   fragment shader and updates its clock and parameters every frame
+//! - A clock can be drawn over any screensaver (see `clock`), with glyphs
+//!   from a per-surface atlas (see `text`); so can text the screensaver
+//!   itself shows (`Screensaver::text`)
 //! - Receives commands from the idle tracker via a calloop channel
 //!
 //! Threading model:
@@ -15,12 +21,18 @@
 //! - The tokio idle loop sends RendererCommands via calloop::channel
 //! - Frame callbacks drive the animation loop (compositor-synced vsync)
 
-use crate::ipc;
-use crate::screensavers;
-use log::{debug, info, warn};
+use crate::config::ScreensaverConfig;
+use crate::screensavers::params::ParamType;
+use crate::screensavers::{self, blank::Blank, header, images, passes, validate, SaverText, Screensaver};
+use crate::{clock, ipc, message, text};
+use log::{debug, error, info, warn};
 use raw_window_handle::{
     RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
 };
+use smithay_client_toolkit::reexports::calloop::{
+    timer::{TimeoutAction, Timer},
+    LoopHandle, RegistrationToken,
+};
 use smithay_client_toolkit::{
     compositor::{CompositorHandler, CompositorState},
     delegate_compositor, delegate_layer, delegate_output, delegate_registry, delegate_seat,
@@ -36,11 +48,13 @@ use smithay_client_toolkit::{
         WaylandSurface,
     },
 };
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use wayland_client::{
     globals::registry_queue_init,
     protocol::{wl_output, wl_seat, wl_surface},
@@ -57,36 +71,161 @@ use wayland_protocols::ext::idle_notify::v1::client::{
 
 /// Commands sent from the idle tracker to the renderer
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RendererCommand {
-    /// Start a screensaver on a specific monitor
+    /// Start a screensaver on a specific monitor, with the monitor's
+    /// effective (global + per-monitor) settings
     Start {
         monitor: String,
-        screensaver: String,
+        screensaver: ScreensaverConfig,
     },
     /// Start screensavers on ALL monitors (session-wide idle)
-    StartAll { screensaver: String },
+    StartAll { screensaver: ScreensaverConfig },
     /// Stop the screensaver on a specific monitor
     Stop { monitor: String },
     /// Stop all screensavers (e.g. session-wide wake)
     StopAll,
     /// A monitor was disconnected; clean up its resources
     MonitorRemoved { monitor: String },
+    /// Session-wide idle settings changed (config reload)
+    SetSessionIdle { enabled: bool, timeout_secs: u64 },
+    /// The away message changed (see `message`); None goes back to the
+    /// configured one
+    SetMessage { text: Option<String> },
     /// Shutdown the renderer
     Shutdown,
 }
 
+/// Lifecycle events published by the renderer back to the idle tracker.
+///
+/// The renderer can tear surfaces down on its own (compositor closes the
+/// layer, output disappears, surface errors), so these events are the source
+/// of truth for whether a screensaver is actually showing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RendererEvent {
+    /// The screensaver surface was configured and its first frame presented
+    Started { monitor: String },
+    /// The screensaver surface was torn down
+    Stopped { monitor: String, reason: StopReason },
+    /// The screensaver could not be started or died while rendering
+    Failed { monitor: String, error: String },
+    /// The screensaver's pipeline couldn't be built; a fallback is showing
+    Degraded { monitor: String, error: String },
+}
+
+/// Why a screensaver surface was torn down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// A Stop/StopAll/Shutdown command asked for it
+    Requested,
+    /// The compositor closed the layer surface
+    Closed,
+    /// The output was disconnected
+    OutputRemoved,
+}
+
 // ---------------------------------------------------------------------------
 // GPU types
 // ---------------------------------------------------------------------------
 
+/// Smallest storage buffer allocated, enough for one `vec4<f32>`
+const MIN_STORAGE_SIZE: usize = 16;
+
+/// Format of offscreen pass textures: filterable, and precise enough for
+/// simulations that accumulate over many frames
+const PASS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
 /// Uniform buffer passed to every screensaver shader
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 struct Uniforms {
     time: f32,
-    _pad: f32,
+    /// Configured overlay opacity. The renderer already applies it when
+    /// blending; shaders may read it, but shouldn't multiply by it again.
+    opacity: f32,
     resolution: [f32; 2],
+    /// Frames rendered since the screensaver started
+    frame: u32,
+    _pad: [u32; 3],
+    /// Local date: year, month (0-11), day, seconds since midnight
+    date: [f32; 4],
+}
+
+/// Local date for the `date` uniform: year, month (0-11), day of the month
+/// and seconds since midnight, like Shadertoy's `iDate`
+fn local_date() -> [f32; 4] {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    let Some(tm) = clock::local_tm(now.as_secs() as i64) else {
+        return [0.0; 4];
+    };
+    let seconds = (tm.tm_hour * 3600 + tm.tm_min * 60 + tm.tm_sec) as f32 + now.subsec_millis() as f32 / 1000.0;
+    [(tm.tm_year + 1900) as f32, tm.tm_mon as f32, tm.tm_mday as f32, seconds]
+}
+
+/// Premultiplied-alpha blending scaled by the blend constant, which
+/// `render_frame` sets to the configured opacity. Every frame starts from a
+/// cleared target, so this amounts to multiplying the shader's output by the
+/// opacity, whatever alpha the shader itself returns.
+const OPACITY_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+/// Shader drawing text over a screensaver
+const TEXT_SHADER: &str = include_str!("../screensavers/shaders/text.wgsl");
+
+/// Uniforms of the text shader
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextUniforms {
+    /// RGB and opacity
+    color: [f32; 4],
+    resolution: [f32; 2],
+    /// Top left corner of the text on screen, in pixels
+    offset: [f32; 2],
+}
+
+/// Corner of a glyph quad
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TextVertex {
+    /// Pixels from the top left of the text
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
+/// Two triangles per glyph
+fn text_vertices(layout: &text::TextLayout) -> Vec<TextVertex> {
+    layout
+        .quads
+        .iter()
+        .flat_map(|quad| {
+            let [left, top, right, bottom] = quad.rect;
+            let [u0, v0, u1, v1] = quad.uv;
+            let corner = |x, y, u, v| TextVertex {
+                position: [x, y],
+                uv: [u, v],
+            };
+            [
+                corner(left, top, u0, v0),
+                corner(left, bottom, u0, v1),
+                corner(right, bottom, u1, v1),
+                corner(right, bottom, u1, v1),
+                corner(right, top, u1, v0),
+                corner(left, top, u0, v0),
+            ]
+        })
+        .collect()
 }
 
 /// Fullscreen quad vertex
@@ -124,7 +263,13 @@ struct GpuContext {
     queue: wgpu::Queue,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
-    bind_group_layout: wgpu::BindGroupLayout,
+    /// Sampler for pass textures (`pass_sampler`)
+    pass_sampler: wgpu::Sampler,
+    /// Sampler for images (`image_sampler`)
+    image_sampler: wgpu::Sampler,
+    /// Set by wgpu's callbacks when the device is gone; the renderer then
+    /// rebuilds the context and every active surface
+    device_lost: Arc<AtomicBool>,
 }
 
 // ---------------------------------------------------------------------------
@@ -136,12 +281,22 @@ struct MonitorSurface {
     layer: LayerSurface,
     /// wgpu surface bound to the layer's wl_surface
     wgpu_surface: Option<wgpu::Surface<'static>>,
-    /// Render pipeline for this monitor's screensaver
-    pipeline: Option<wgpu::RenderPipeline>,
+    /// Render pipelines for this monitor's screensaver
+    pipeline: Option<SaverPipelines>,
     /// Uniform buffer
     uniform_buffer: wgpu::Buffer,
-    /// Bind group
-    bind_group: wgpu::BindGroup,
+    /// The running screensaver
+    saver: Box<dyn Screensaver>,
+    /// Complete WGSL source (pipeline is built once the format is known)
+    shader_source: String,
+    /// The screensaver's parameters, storage buffers and their bind group
+    bindings: SaverBindings,
+    /// Pass textures sized to the surface and images, bound together
+    textures: Option<SaverTextures>,
+    /// The clock, once the surface format is known, if it's enabled
+    clock: Option<TextOverlay>,
+    /// Text the screensaver shows, once it has shown any
+    text: Option<TextOverlay>,
     /// Surface format
     format: Option<wgpu::TextureFormat>,
     /// Current dimensions
@@ -149,10 +304,93 @@ struct MonitorSurface {
     height: u32,
     /// Whether we've received the first configure
     configured: bool,
-    /// When the screensaver started (for time uniform)
+    /// When the screensaver started (a reloaded one catches up to it)
     start_time: Instant,
-    /// Name of the active screensaver
-    screensaver_name: String,
+    /// Frames rendered so far (for the frame uniform)
+    frame_count: u32,
+    /// Settings of the active screensaver
+    screensaver: ScreensaverConfig,
+    /// When the last frame was rendered (for fps pacing)
+    last_frame: Option<Instant>,
+    /// Pending fps-pacing timer that will request the next frame callback
+    frame_timer: Option<RegistrationToken>,
+    /// Whether `RendererEvent::Started` has been published for this surface
+    reported_started: bool,
+}
+
+impl MonitorSurface {
+    /// (Re)initialize the running saver for a `width`x`height` surface
+    fn init_saver(&mut self, width: u32, height: u32, message: Option<&str>) {
+        let options = saver_options(self.saver.as_ref(), &self.screensaver.options, message);
+        self.saver.init(width, height, &options);
+    }
+}
+
+/// GPU resources bound for a screensaver's shader
+struct SaverBindings {
+    /// Layout the pipeline was built with
+    layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    storage_buffers: Vec<wgpu::Buffer>,
+    bind_group: wgpu::BindGroup,
+    /// Offscreen passes declared in the shader's header, in order
+    passes: Vec<String>,
+    /// The shader's images, loaded from its options
+    images: Vec<ImageTexture>,
+    /// Layout of the texture bind group, if there are passes or images
+    texture_layout: Option<wgpu::BindGroupLayout>,
+}
+
+/// An image and the view it's sampled through
+struct ImageTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+/// Render pipelines for a screensaver: one per offscreen pass, then
+/// `fs_main` onto the surface
+struct SaverPipelines {
+    passes: Vec<wgpu::RenderPipeline>,
+    main: wgpu::RenderPipeline,
+}
+
+/// Persistent textures for a screensaver's passes, rebuilt (and so
+/// cleared) on resize, and the bind groups they are sampled through along
+/// with the images
+struct SaverTextures {
+    /// Two textures per pass: frames alternate which one is written
+    views: Vec<[wgpu::TextureView; 2]>,
+    /// Texture bind groups by frame parity, then stage (the passes, then
+    /// `fs_main`); see `pass_texture`
+    bind_groups: [Vec<wgpu::BindGroup>; 2],
+}
+
+/// Text drawn over a screensaver: its glyph atlas, on the CPU and as a
+/// texture, and the quads of the current text
+struct TextOverlay {
+    atlas: text::GlyphAtlas,
+    /// Font file (None for the built-in one) and size the atlas is for
+    font: Option<String>,
+    px: f32,
+    align: text::Align,
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: Option<wgpu::Buffer>,
+    vertex_count: u32,
+    /// The text laid out, and its size in pixels
+    text: String,
+    size: [f32; 2],
+}
+
+impl TextOverlay {
+    /// Whether the atlas is for text in this font, size and alignment
+    fn draws(&self, style: &SaverText) -> bool {
+        self.font.as_deref() == style.font && self.px == style.size && self.align == style.align
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -201,56 +439,83 @@ pub struct WaylandState {
     // Pending commands from the idle tracker (processed in the event loop)
     pending_commands: Vec<RendererCommand>,
 
+    // Lifecycle events back to the idle tracker (None in preview mode)
+    event_tx: Option<mpsc::UnboundedSender<RendererEvent>>,
+
+    // calloop handle for fps-pacing timers
+    loop_handle: Option<LoopHandle<'static, WaylandState>>,
+
     // Session-wide idle (ext-idle-notify-v1)
     idle_notifier: Option<ExtIdleNotifierV1>,
     idle_notification: Option<ExtIdleNotificationV1>,
     session_idle_config: SessionIdleConfig,
+
+    // Away message set via the control socket, in place of the configured one
+    message: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
 // Shader loading
 // ---------------------------------------------------------------------------
 
-/// Common vertex shader source (shared by all screensavers)
-const COMMON_SHADER: &str = include_str!("../screensavers/shaders/common.wgsl");
-
-/// Get the fragment shader source for a named screensaver.
-/// Checks custom shader directory first, then falls back to built-in.
-fn get_fragment_shader(name: &str) -> String {
-    // Try custom shader first
-    if let Some(dir) = screensavers::custom_shader_dir() {
-        let path = dir.join(format!("{}.wgsl", name));
-        if path.is_file() {
-            match screensavers::load_custom_shader(&path) {
-                Ok(source) => {
-                    info!("Loaded custom shader '{}' from {}", name, path.display());
-                    return source;
-                }
-                Err(e) => {
-                    warn!("{}", e);
-                    warn!("Falling back to built-in for '{}'", name);
-                }
-            }
-        }
-    }
+/// Complete WGSL source for a screensaver: the common vertex shader, its
+/// parameter block and its fragment shader with imports expanded
+fn shader_source(saver: &dyn Screensaver) -> Result<String, String> {
+    let fragment = saver.fragment_shader();
+    let header = header::parse(fragment)
+        .map_err(|e| format!("Invalid header in '{}': {}", saver.name(), e))?;
+    let fragment = screensavers::expand_imports(saver.name(), fragment)?;
+    Ok(format!("{}{}", screensavers::prelude(&header), fragment))
+}
+
+/// Instantiate a screensaver and build its shader source
+fn load_saver(name: &str) -> Result<(Box<dyn Screensaver>, String), String> {
+    let saver = screensavers::create(name)?;
+    let source = shader_source(saver.as_ref())?;
+    Ok((saver, source))
+}
 
-    // Built-in shaders
-    match name {
-        "blank" => include_str!("../screensavers/shaders/blank.wgsl").to_string(),
-        "matrix" => include_str!("../screensavers/shaders/matrix.wgsl").to_string(),
-        "plasmula" => include_str!("../screensavers/shaders/plasmula.wgsl").to_string(),
-        "starfield" => include_str!("../screensavers/shaders/starfield.wgsl").to_string(),
-        _ => {
-            warn!("Unknown screensaver '{}', falling back to blank", name);
-            include_str!("../screensavers/shaders/blank.wgsl").to_string()
+/// The blank screensaver, used when another can't be loaded or built
+fn fallback_saver() -> (Box<dyn Screensaver>, String) {
+    let saver = Box::new(Blank::new());
+    let source = shader_source(saver.as_ref()).expect("the blank shader is valid");
+    (saver, source)
+}
+
+/// Whether a screensaver declares the string parameter the away message
+/// is shown through
+fn takes_message(saver: &dyn Screensaver) -> bool {
+    header::parse(saver.fragment_shader())
+        .ok()
+        .and_then(|header| header.param(message::OPTION).map(|param| param.ty == ParamType::Str))
+        .unwrap_or(false)
+}
+
+/// A screensaver's options, with the away message (if one is set and the
+/// saver takes it) in place of the configured one
+fn saver_options<'a>(
+    saver: &dyn Screensaver,
+    options: &'a HashMap<String, toml::Value>,
+    message: Option<&str>,
+) -> Cow<'a, HashMap<String, toml::Value>> {
+    match message {
+        Some(text) if takes_message(saver) => {
+            let mut options = options.clone();
+            options.insert(message::OPTION.to_string(), toml::Value::String(text.to_string()));
+            Cow::Owned(options)
         }
+        _ => Cow::Borrowed(options),
     }
 }
 
-/// Combine common vertex shader with a screensaver's fragment shader
-fn build_shader_source(screensaver_name: &str) -> String {
-    let fragment = get_fragment_shader(screensaver_name);
-    format!("{}\n{}", COMMON_SHADER, fragment)
+/// Like `load_saver`, but unknown or invalid screensavers are replaced by
+/// blank rather than risking a failed pipeline
+fn build_saver(name: &str) -> (Box<dyn Screensaver>, String) {
+    load_saver(name).unwrap_or_else(|e| {
+        warn!("{}", e);
+        warn!("Falling back to blank for '{}'", name);
+        fallback_saver()
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -285,6 +550,25 @@ impl GpuContext {
         let (device, queue) =
             pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))?;
 
+        // Without a handler wgpu panics on errors outside an error scope.
+        // Validation errors are logged; anything worse means the device
+        // can't be trusted anymore.
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let lost = device_lost.clone();
+        device.on_uncaptured_error(Box::new(move |e| {
+            error!("Uncaptured GPU error: {}", e);
+            if !matches!(e, wgpu::Error::Validation { .. }) {
+                lost.store(true, Ordering::SeqCst);
+            }
+        }));
+        let lost = device_lost.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            if reason != wgpu::DeviceLostReason::Destroyed {
+                error!("GPU device lost: {}", message);
+                lost.store(true, Ordering::SeqCst);
+            }
+        });
+
         // Create shared vertex/index buffers for the fullscreen quad
         use wgpu::util::DeviceExt;
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -298,19 +582,19 @@ impl GpuContext {
             usage: wgpu::BufferUsages::INDEX,
         });
 
-        // Shared bind group layout for the uniform buffer
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("uniforms_layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
+        let pass_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("pass_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let image_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("image_sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
         });
 
         Ok(Self {
@@ -320,9 +604,666 @@ impl GpuContext {
             queue,
             vertex_buffer,
             index_buffer,
-            bind_group_layout,
+            pass_sampler,
+            image_sampler,
+            device_lost,
         })
     }
+
+    /// Bind group layout for a screensaver: common uniforms, its parameter
+    /// block and `storage_buffers` read-only storage buffers
+    fn create_bind_group_layout(&self, storage_buffers: usize) -> wgpu::BindGroupLayout {
+        let buffer = |binding, visibility, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let mut entries = vec![
+            buffer(
+                0,
+                wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                wgpu::BufferBindingType::Uniform,
+            ),
+            buffer(1, wgpu::ShaderStages::FRAGMENT, wgpu::BufferBindingType::Uniform),
+        ];
+        entries.extend((0..storage_buffers).map(|i| {
+            buffer(
+                validate::FIRST_STORAGE_BINDING + i as u32,
+                wgpu::ShaderStages::FRAGMENT,
+                wgpu::BufferBindingType::Storage { read_only: true },
+            )
+        }));
+        self.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("uniforms_layout"),
+            entries: &entries,
+        })
+    }
+
+    /// Bind group layout for `passes` pass textures and `images` images,
+    /// each kind with its sampler
+    fn create_texture_layout(&self, passes: usize, images: usize) -> wgpu::BindGroupLayout {
+        let sampler = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let texture = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let mut entries = vec![sampler(0), sampler(images::sampler_binding(passes))];
+        entries.extend((0..passes).map(|i| texture(passes::binding(i))));
+        entries.extend((0..images).map(|i| texture(images::binding(passes, i))));
+        self.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("textures_layout"),
+            entries: &entries,
+        })
+    }
+
+    /// Texture holding an image for sampling
+    fn create_image(&self, label: &str, image: &image::RgbaImage) -> ImageTexture {
+        use wgpu::util::DeviceExt;
+        let (width, height) = image.dimensions();
+        let texture = self.device.create_texture_with_data(
+            &self.queue,
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            image.as_raw(),
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        ImageTexture { texture, view }
+    }
+
+    /// Load a screensaver's images as set in its options. One that isn't
+    /// set or can't be loaded is a transparent pixel.
+    fn load_images(
+        &self,
+        saver: &str,
+        decls: &[images::ImageDecl],
+        options: &HashMap<String, toml::Value>,
+    ) -> Vec<ImageTexture> {
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        decls
+            .iter()
+            .map(|decl| {
+                let image = match decl.path(options) {
+                    Some(path) => images::load(&path, max_size).unwrap_or_else(|e| {
+                        warn!("Screensaver '{}': image '{}': {}", saver, decl.name, e);
+                        image::RgbaImage::new(1, 1)
+                    }),
+                    None => {
+                        // Fine for savers that provide their own (see
+                        // `Screensaver::take_images`)
+                        debug!("Screensaver '{}': image '{}' isn't set", saver, decl.name);
+                        image::RgbaImage::new(1, 1)
+                    }
+                };
+                self.create_image(&decl.name, &image)
+            })
+            .collect()
+    }
+
+    /// Buffer for a screensaver's parameter block, rewritten every frame
+    fn create_params_buffer(&self, params: &[u8]) -> wgpu::Buffer {
+        use wgpu::util::DeviceExt;
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: params,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+    }
+
+    /// Storage buffer holding `data`, rewritten every frame
+    fn create_storage_buffer(&self, data: &[u8]) -> wgpu::Buffer {
+        use wgpu::util::DeviceExt;
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("storage"),
+                contents: &storage_contents(data),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            })
+    }
+
+    /// Everything a screensaver's shader binds, filled with its current data
+    /// and the images its options name
+    fn create_bindings(
+        &self,
+        uniform_buffer: &wgpu::Buffer,
+        saver: &dyn Screensaver,
+        options: &HashMap<String, toml::Value>,
+    ) -> SaverBindings {
+        let storage = saver.storage();
+        let layout = self.create_bind_group_layout(storage.len());
+        let params_buffer = self.create_params_buffer(saver.params());
+        let storage_buffers: Vec<wgpu::Buffer> =
+            storage.iter().map(|data| self.create_storage_buffer(data)).collect();
+        let bind_group = self.create_bind_group(&layout, uniform_buffer, &params_buffer, &storage_buffers);
+        let header = header::parse(saver.fragment_shader()).unwrap_or_default();
+        let images = self.load_images(saver.name(), &header.images, options);
+        let texture_layout = (!header.passes.is_empty() || !images.is_empty())
+            .then(|| self.create_texture_layout(header.passes.len(), images.len()));
+        SaverBindings {
+            layout,
+            params_buffer,
+            storage_buffers,
+            bind_group,
+            passes: header.passes,
+            images,
+            texture_layout,
+        }
+    }
+
+    /// Textures for a screensaver's passes at the given size, with the bind
+    /// groups every stage samples them and the images through. None if the
+    /// shader has neither.
+    fn create_textures(&self, bindings: &SaverBindings, width: u32, height: u32) -> Option<SaverTextures> {
+        let layout = bindings.texture_layout.as_ref()?;
+        let views: Vec<[wgpu::TextureView; 2]> = bindings
+            .passes
+            .iter()
+            .map(|name| {
+                [0, 1].map(|_| {
+                    self.device
+                        .create_texture(&wgpu::TextureDescriptor {
+                            label: Some(name),
+                            size: wgpu::Extent3d {
+                                width,
+                                height,
+                                depth_or_array_layers: 1,
+                            },
+                            mip_level_count: 1,
+                            sample_count: 1,
+                            dimension: wgpu::TextureDimension::D2,
+                            format: PASS_FORMAT,
+                            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                                | wgpu::TextureUsages::TEXTURE_BINDING,
+                            view_formats: &[],
+                        })
+                        .create_view(&wgpu::TextureViewDescriptor::default())
+                })
+            })
+            .collect();
+        let bind_groups = self.create_texture_bind_groups(layout, bindings, &views);
+        Some(SaverTextures { views, bind_groups })
+    }
+
+    /// Texture bind groups for every stage and frame parity, sampling the
+    /// given pass textures and the current images
+    fn create_texture_bind_groups(
+        &self,
+        layout: &wgpu::BindGroupLayout,
+        bindings: &SaverBindings,
+        views: &[[wgpu::TextureView; 2]],
+    ) -> [Vec<wgpu::BindGroup>; 2] {
+        let stages = bindings.passes.len() + 1;
+        [0, 1].map(|parity| {
+            (0..stages)
+                .map(|stage| {
+                    let passes = bindings.passes.len();
+                    let mut entries = vec![
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::Sampler(&self.pass_sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: images::sampler_binding(passes),
+                            resource: wgpu::BindingResource::Sampler(&self.image_sampler),
+                        },
+                    ];
+                    entries.extend(views.iter().enumerate().map(|(pass, textures)| {
+                        wgpu::BindGroupEntry {
+                            binding: passes::binding(pass),
+                            resource: wgpu::BindingResource::TextureView(
+                                &textures[pass_texture(stage, pass, parity)],
+                            ),
+                        }
+                    }));
+                    entries.extend(bindings.images.iter().enumerate().map(|(i, image)| {
+                        wgpu::BindGroupEntry {
+                            binding: images::binding(passes, i),
+                            resource: wgpu::BindingResource::TextureView(&image.view),
+                        }
+                    }));
+                    self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("textures_bind_group"),
+                        layout,
+                        entries: &entries,
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Swap in images a screensaver decoded itself, by index among its
+    /// `@image`s. One the size of the texture it replaces is written into
+    /// it (animations replace one every frame); otherwise the texture bind
+    /// groups are rebuilt around a new texture.
+    fn replace_images(
+        &self,
+        bindings: &mut SaverBindings,
+        textures: Option<&mut SaverTextures>,
+        replaced: Vec<(usize, Arc<image::RgbaImage>)>,
+    ) {
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        let mut rebuild = false;
+        for (index, image) in replaced {
+            if index >= bindings.images.len() {
+                warn!("Screensaver provided image {} but declares {}", index, bindings.images.len());
+                continue;
+            }
+            let image = if image.width() > max_size || image.height() > max_size {
+                let resized = image::DynamicImage::ImageRgba8(Arc::unwrap_or_clone(image))
+                    .resize(max_size, max_size, image::imageops::FilterType::Triangle)
+                    .into_rgba8();
+                Arc::new(resized)
+            } else {
+                image
+            };
+            let texture = &bindings.images[index].texture;
+            if (texture.width(), texture.height()) == image.dimensions() {
+                self.queue.write_texture(
+                    wgpu::TexelCopyTextureInfo {
+                        texture,
+                        mip_level: 0,
+                        origin: wgpu::Origin3d::ZERO,
+                        aspect: wgpu::TextureAspect::All,
+                    },
+                    image.as_raw(),
+                    wgpu::TexelCopyBufferLayout {
+                        offset: 0,
+                        bytes_per_row: Some(image.width() * 4),
+                        rows_per_image: Some(image.height()),
+                    },
+                    texture.size(),
+                );
+            } else {
+                bindings.images[index] = self.create_image("image", &image);
+                rebuild = true;
+            }
+        }
+        if rebuild
+            && let (Some(textures), Some(layout)) = (textures, bindings.texture_layout.as_ref())
+        {
+            textures.bind_groups = self.create_texture_bind_groups(layout, bindings, &textures.views);
+        }
+    }
+
+    fn create_bind_group(
+        &self,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        params_buffer: &wgpu::Buffer,
+        storage_buffers: &[wgpu::Buffer],
+    ) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: params_buffer.as_entire_binding(),
+            },
+        ];
+        entries.extend(storage_buffers.iter().enumerate().map(|(i, buffer)| {
+            wgpu::BindGroupEntry {
+                binding: validate::FIRST_STORAGE_BINDING + i as u32,
+                resource: buffer.as_entire_binding(),
+            }
+        }));
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("uniforms_bind_group"),
+            layout,
+            entries: &entries,
+        })
+    }
+
+    /// Upload a screensaver's parameters and storage buffers for the next
+    /// frame. Storage buffers that changed size are reallocated, which
+    /// needs a new bind group.
+    fn upload(&self, bindings: &mut SaverBindings, uniform_buffer: &wgpu::Buffer, saver: &dyn Screensaver) {
+        self.queue.write_buffer(&bindings.params_buffer, 0, saver.params());
+
+        let mut resized = false;
+        for (buffer, data) in bindings.storage_buffers.iter_mut().zip(saver.storage()) {
+            let contents = storage_contents(data);
+            if buffer.size() == contents.len() as u64 {
+                self.queue.write_buffer(buffer, 0, &contents);
+            } else {
+                *buffer = self.create_storage_buffer(data);
+                resized = true;
+            }
+        }
+        if resized {
+            bindings.bind_group = self.create_bind_group(
+                &bindings.layout,
+                uniform_buffer,
+                &bindings.params_buffer,
+                &bindings.storage_buffers,
+            );
+        }
+    }
+
+    /// Compile `source` and build its pipelines inside an error scope, so a
+    /// bad shader is reported here instead of reaching the uncaptured-error
+    /// handler
+    fn try_create_pipelines(
+        &self,
+        label: &str,
+        source: &str,
+        format: wgpu::TextureFormat,
+        bindings: &SaverBindings,
+    ) -> Result<SaverPipelines, String> {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = self
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
+        let layouts: Vec<&wgpu::BindGroupLayout> =
+            std::iter::once(&bindings.layout).chain(&bindings.texture_layout).collect();
+        // Passes replace their texture's contents; only the final image is
+        // blended onto the desktop
+        let passes = bindings
+            .passes
+            .iter()
+            .map(|name| {
+                let target = wgpu::ColorTargetState {
+                    format: PASS_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                };
+                let entry_point = passes::entry_point(name);
+                WaylandState::create_pipeline(&self.device, &layouts, &shader, &entry_point, target)
+            })
+            .collect();
+        let target = wgpu::ColorTargetState {
+            format,
+            blend: Some(OPACITY_BLENDING),
+            write_mask: wgpu::ColorWrites::ALL,
+        };
+        let main = WaylandState::create_pipeline(&self.device, &layouts, &shader, "fs_main", target);
+        match pollster::block_on(self.device.pop_error_scope()) {
+            None => Ok(SaverPipelines { passes, main }),
+            Some(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Draw the fullscreen quad with the pass's current pipeline and bind groups
+    fn draw_quad(&self, pass: &mut wgpu::RenderPass) {
+        pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        pass.draw_indexed(0..6, 0, 0..1);
+    }
+
+    /// Text overlay in a font file (or the built-in font) at `px` pixels
+    /// high, drawn onto a surface of the given format. It starts out empty;
+    /// see `set_text`.
+    fn create_text_overlay(
+        &self,
+        font: Option<&str>,
+        px: f32,
+        align: text::Align,
+        format: wgpu::TextureFormat,
+    ) -> TextOverlay {
+        let atlas = text::GlyphAtlas::new(text::font_or_fallback(font), px);
+        let layout = self.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("text_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let shader = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("text"),
+            source: wgpu::ShaderSource::Wgsl(TEXT_SHADER.into()),
+        });
+        let pipeline_layout = self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("text_pipeline_layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("text_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<TextVertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
+                }],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let uniform_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("text_uniforms"),
+            size: std::mem::size_of::<TextUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let sampler = self.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("atlas_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let texture = self.create_atlas_texture(atlas.size());
+        let bind_group = self.create_text_bind_group(&layout, &uniform_buffer, &sampler, &texture);
+        TextOverlay {
+            atlas,
+            font: font.map(str::to_string),
+            px,
+            align,
+            pipeline,
+            layout,
+            uniform_buffer,
+            sampler,
+            texture,
+            bind_group,
+            vertex_buffer: None,
+            vertex_count: 0,
+            text: String::new(),
+            size: [0.0; 2],
+        }
+    }
+
+    /// Single-channel texture for a glyph atlas
+    fn create_atlas_texture(&self, size: u32) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("glyph_atlas"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn create_text_bind_group(
+        &self,
+        layout: &wgpu::BindGroupLayout,
+        uniform_buffer: &wgpu::Buffer,
+        sampler: &wgpu::Sampler,
+        texture: &wgpu::Texture,
+    ) -> wgpu::BindGroup {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    /// Show `text` in an overlay. Laying it out only happens when it
+    /// changes, and the atlas is only uploaded when glyphs were added.
+    fn set_text(&self, overlay: &mut TextOverlay, text: &str) {
+        if overlay.text != text || overlay.vertex_buffer.is_none() {
+            use wgpu::util::DeviceExt;
+            let layout = overlay.atlas.layout(text, overlay.align);
+            let vertices = text_vertices(&layout);
+            overlay.vertex_buffer = (!vertices.is_empty()).then(|| {
+                self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("text_vertices"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                })
+            });
+            overlay.vertex_count = vertices.len() as u32;
+            overlay.text = text.to_string();
+            overlay.size = layout.size;
+        }
+
+        if overlay.atlas.take_dirty() {
+            let size = overlay.atlas.size();
+            if overlay.texture.width() != size {
+                overlay.texture = self.create_atlas_texture(size);
+                overlay.bind_group = self.create_text_bind_group(
+                    &overlay.layout,
+                    &overlay.uniform_buffer,
+                    &overlay.sampler,
+                    &overlay.texture,
+                );
+            }
+            self.queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &overlay.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                overlay.atlas.pixels(),
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size),
+                    rows_per_image: Some(size),
+                },
+                overlay.texture.size(),
+            );
+        }
+    }
+
+    /// Draw an overlay's text where its uniforms put it
+    fn draw_text(&self, pass: &mut wgpu::RenderPass, overlay: &TextOverlay) {
+        let Some(ref vertices) = overlay.vertex_buffer else {
+            return;
+        };
+        pass.set_pipeline(&overlay.pipeline);
+        pass.set_bind_group(0, &overlay.bind_group, &[]);
+        pass.set_vertex_buffer(0, vertices.slice(..));
+        pass.draw(0..overlay.vertex_count, 0..1);
+    }
+}
+
+/// Which of a pass's two textures `stage` samples on a frame of the given
+/// parity. Passes before it have already written this frame's texture
+/// (`parity`); it and later ones still hold the last frame in the other.
+fn pass_texture(stage: usize, pass: usize, parity: usize) -> usize {
+    if pass < stage {
+        parity
+    } else {
+        1 - parity
+    }
+}
+
+/// Storage buffer contents: copies must be a multiple of 4 bytes, and a
+/// runtime-sized array binding needs room for at least one element
+fn storage_contents(data: &[u8]) -> std::borrow::Cow<'_, [u8]> {
+    let size = data.len().max(MIN_STORAGE_SIZE).next_multiple_of(4);
+    if size == data.len() {
+        return data.into();
+    }
+    let mut padded = data.to_vec();
+    padded.resize(size, 0);
+    padded.into()
 }
 
 // ---------------------------------------------------------------------------
@@ -351,8 +1292,9 @@ impl WaylandState {
         let seat_state = SeatState::new(&globals, &qh);
         let registry_state = RegistryState::new(&globals);
 
-        // Bind ext-idle-notify-v1 if available and session idle is enabled
-        let idle_notifier: Option<ExtIdleNotifierV1> = if session_idle_config.enabled {
+        // Bind ext-idle-notify-v1 if available. It is bound even when session
+        // idle is disabled so a config reload can turn it on later.
+        let idle_notifier: Option<ExtIdleNotifierV1> =
             match globals.bind::<ExtIdleNotifierV1, Self, ()>(&qh, 1..=1, ()) {
                 Ok(notifier) => {
                     info!("ext-idle-notify-v1 bound successfully");
@@ -365,18 +1307,19 @@ impl WaylandState {
                     );
                     None
                 }
-            }
-        } else {
+            };
+        if !session_idle_config.enabled {
             info!("Session-wide idle disabled by config");
-            None
-        };
+        }
 
         let gpu = GpuContext::new(&conn)?;
 
         // Create idle notification using an already-enumerated seat.
         // SeatState::new() processes existing seats during registry_queue_init,
         // so new_seat() never fires for them. We must create the notification here.
-        let idle_notification = if let Some(ref notifier) = idle_notifier {
+        let idle_notification = if let Some(ref notifier) = idle_notifier
+            && session_idle_config.enabled
+        {
             let seats = seat_state.seats();
             if let Some(seat) = seats.into_iter().next() {
                 let timeout_ms = (session_idle_config.timeout_secs * 1000) as u32;
@@ -409,9 +1352,12 @@ impl WaylandState {
                 conn,
                 exit: false,
                 pending_commands: Vec::new(),
+                event_tx: None,
+                loop_handle: None,
                 idle_notifier,
                 idle_notification,
                 session_idle_config,
+                message: None,
             },
             event_queue,
             conn_clone,
@@ -428,6 +1374,25 @@ impl WaylandState {
         !self.output_map.is_empty()
     }
 
+    /// Publish surface lifecycle events to the given channel
+    pub fn set_event_sender(&mut self, tx: mpsc::UnboundedSender<RendererEvent>) {
+        self.event_tx = Some(tx);
+    }
+
+    /// Give the renderer access to the event loop for frame pacing timers
+    pub fn set_loop_handle(&mut self, handle: LoopHandle<'static, WaylandState>) {
+        self.loop_handle = Some(handle);
+    }
+
+    /// Send a lifecycle event to the idle tracker, if anyone is listening
+    fn publish(&self, event: RendererEvent) {
+        if let Some(ref tx) = self.event_tx
+            && tx.send(event).is_err()
+        {
+            debug!("Renderer event receiver dropped");
+        }
+    }
+
     /// Queue a command for processing on the next event loop iteration
     pub fn queue_command(&mut self, cmd: RendererCommand) {
         self.pending_commands.push(cmd);
@@ -435,6 +1400,7 @@ impl WaylandState {
 
     /// Process all pending commands
     pub fn process_commands(&mut self) {
+        self.recover_device();
         let commands: Vec<_> = self.pending_commands.drain(..).collect();
         for cmd in commands {
             match cmd {
@@ -449,13 +1415,22 @@ impl WaylandState {
                     self.start_all(&screensaver);
                 }
                 RendererCommand::Stop { monitor } => {
-                    self.stop_screensaver(&monitor);
+                    self.stop_screensaver(&monitor, StopReason::Requested);
                 }
                 RendererCommand::StopAll => {
                     self.stop_all();
                 }
                 RendererCommand::MonitorRemoved { monitor } => {
-                    self.stop_screensaver(&monitor);
+                    self.stop_screensaver(&monitor, StopReason::OutputRemoved);
+                }
+                RendererCommand::SetSessionIdle {
+                    enabled,
+                    timeout_secs,
+                } => {
+                    self.set_session_idle(enabled, timeout_secs);
+                }
+                RendererCommand::SetMessage { text } => {
+                    self.set_message(text);
                 }
                 RendererCommand::Shutdown => {
                     self.stop_all();
@@ -465,6 +1440,60 @@ impl WaylandState {
         }
     }
 
+    /// Recreate the ext-idle-notify-v1 notification with new settings
+    fn set_session_idle(&mut self, enabled: bool, timeout_secs: u64) {
+        if let Some(notification) = self.idle_notification.take() {
+            notification.destroy();
+        }
+        // The old notification can't report Resumed anymore
+        self.session_idle_config
+            .session_idle_active
+            .store(false, Ordering::SeqCst);
+        self.session_idle_config.enabled = enabled;
+        self.session_idle_config.timeout_secs = timeout_secs;
+
+        if !enabled {
+            info!("Session-wide idle disabled by config");
+            return;
+        }
+        let Some(ref notifier) = self.idle_notifier else {
+            return;
+        };
+        if let Some(seat) = self.seat_state.seats().next() {
+            info!(
+                "Recreating ext-idle-notify-v1 notification (timeout: {}s)",
+                timeout_secs
+            );
+            let timeout_ms = (timeout_secs * 1000) as u32;
+            self.idle_notification =
+                Some(notifier.get_idle_notification(timeout_ms, &seat, &self.qh, ()));
+        }
+    }
+
+    /// Show a new away message on every screensaver that has the option.
+    /// Surfaces that don't animate are redrawn straight away.
+    fn set_message(&mut self, text: Option<String>) {
+        match text {
+            Some(ref text) => info!("Away message set: {:?}", text),
+            None => info!("Away message cleared"),
+        }
+        self.message = text;
+
+        let mut redraw = Vec::new();
+        for (name, surface) in self.surfaces.iter_mut() {
+            if surface.width == 0 || !takes_message(surface.saver.as_ref()) {
+                continue;
+            }
+            surface.init_saver(surface.width, surface.height, self.message.as_deref());
+            if frame_rate(&surface.screensaver) == 0 {
+                redraw.push(name.clone());
+            }
+        }
+        for name in redraw {
+            self.render_frame(&name);
+        }
+    }
+
     /// Find the wl_output for a monitor name
     fn find_output(&self, name: &str) -> Option<wl_output::WlOutput> {
         self.output_map
@@ -477,15 +1506,29 @@ impl WaylandState {
     fn start_screensaver(
         &mut self,
         output_name: &str,
-        screensaver_name: &str,
+        screensaver: &ScreensaverConfig,
         qh: &QueueHandle<Self>,
     ) {
-        // Don't start if already active
-        if self.surfaces.contains_key(output_name) {
+        // Don't start if already active, but re-confirm it so the idle
+        // tracker's view stays in sync
+        if let Some(surface) = self.surfaces.get(output_name) {
             debug!(
                 "Screensaver already active on {}, ignoring start",
                 output_name
             );
+            if surface.reported_started {
+                self.publish(RendererEvent::Started {
+                    monitor: output_name.to_string(),
+                });
+            }
+            return;
+        }
+
+        if self.gpu.device_lost.load(Ordering::SeqCst) {
+            self.publish(RendererEvent::Failed {
+                monitor: output_name.to_string(),
+                error: "GPU device unavailable".to_string(),
+            });
             return;
         }
 
@@ -496,13 +1539,17 @@ impl WaylandState {
                     "Cannot start screensaver: output '{}' not found",
                     output_name
                 );
+                self.publish(RendererEvent::Failed {
+                    monitor: output_name.to_string(),
+                    error: "output not found".to_string(),
+                });
                 return;
             }
         };
 
         info!(
             "Creating layer surface for screensaver '{}' on {}",
-            screensaver_name, output_name
+            screensaver.name, output_name
         );
 
         // Create a wl_surface and layer surface
@@ -532,18 +1579,11 @@ impl WaylandState {
             mapped_at_creation: false,
         });
 
-        // Create bind group
-        let bind_group = self
-            .gpu
-            .device
-            .create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("uniforms_bind_group"),
-                layout: &self.gpu.bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                }],
-            });
+        // The screensaver, its shader and the bind group holding its
+        // parameter block (filled once the surface size is known)
+        let (saver, shader_source) = build_saver(&screensaver.name);
+        let options = saver_options(saver.as_ref(), &screensaver.options, self.message.as_deref());
+        let bindings = self.gpu.create_bindings(&uniform_buffer, saver.as_ref(), &options);
 
         self.surfaces.insert(
             output_name.to_string(),
@@ -552,13 +1592,22 @@ impl WaylandState {
                 wgpu_surface: None,
                 pipeline: None,
                 uniform_buffer,
-                bind_group,
+                saver,
+                shader_source,
+                bindings,
+                textures: None,
+                clock: None,
+                text: None,
                 format: None,
                 width: 0,
                 height: 0,
                 configured: false,
                 start_time: Instant::now(),
-                screensaver_name: screensaver_name.to_string(),
+                frame_count: 0,
+                screensaver: screensaver.clone(),
+                last_frame: None,
+                frame_timer: None,
+                reported_started: false,
             },
         );
 
@@ -577,16 +1626,18 @@ impl WaylandState {
         }
     }
 
-    /// Create a render pipeline for a screensaver shader
+    /// Create a render pipeline for one of a screensaver shader's fragment
+    /// entry points
     fn create_pipeline(
         device: &wgpu::Device,
-        bind_group_layout: &wgpu::BindGroupLayout,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
         shader: &wgpu::ShaderModule,
-        format: wgpu::TextureFormat,
+        entry_point: &str,
+        target: wgpu::ColorTargetState,
     ) -> wgpu::RenderPipeline {
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("screensaver_pipeline_layout"),
-            bind_group_layouts: &[bind_group_layout],
+            bind_group_layouts,
             push_constant_ranges: &[],
         });
 
@@ -609,12 +1660,8 @@ impl WaylandState {
             },
             fragment: Some(wgpu::FragmentState {
                 module: shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
+                entry_point: Some(entry_point),
+                targets: &[Some(target)],
                 compilation_options: Default::default(),
             }),
             primitive: wgpu::PrimitiveState {
@@ -634,27 +1681,145 @@ impl WaylandState {
     }
 
     /// Stop the screensaver on a specific monitor
-    fn stop_screensaver(&mut self, output_name: &str) {
-        if let Some(mut surface) = self.surfaces.remove(output_name) {
-            info!("Stopping screensaver on {}", output_name);
-            // Drop order matters: wgpu surface before layer surface
-            if let Some(wgpu_surface) = surface.wgpu_surface.take() {
-                drop(wgpu_surface);
-            }
-            drop(surface.layer);
+    fn stop_screensaver(&mut self, output_name: &str, reason: StopReason) {
+        if self.remove_surface(output_name) {
+            info!("Stopped screensaver on {} ({:?})", output_name, reason);
+            self.publish(RendererEvent::Stopped {
+                monitor: output_name.to_string(),
+                reason,
+            });
+        }
+    }
 
-            // Restore cursor when last screensaver deactivates
-            if self.surfaces.is_empty() {
-                std::thread::spawn(|| {
-                    let rt = tokio::runtime::Builder::new_current_thread()
-                        .enable_io()
-                        .build()
-                        .unwrap();
-                    if let Err(e) = rt.block_on(ipc::show_cursor()) {
-                        warn!("Failed to show cursor: {}", e);
+    /// Tear down a monitor's screensaver after an unrecoverable error
+    fn fail_screensaver(&mut self, output_name: &str, error: String) {
+        warn!("Screensaver on {} failed: {}", output_name, error);
+        self.remove_surface(output_name);
+        self.publish(RendererEvent::Failed {
+            monitor: output_name.to_string(),
+            error,
+        });
+    }
+
+    /// Drop a monitor's surface resources. Returns false if none existed.
+    fn remove_surface(&mut self, output_name: &str) -> bool {
+        let Some(mut surface) = self.surfaces.remove(output_name) else {
+            return false;
+        };
+
+        if let Some(token) = surface.frame_timer.take()
+            && let Some(ref handle) = self.loop_handle
+        {
+            handle.remove(token);
+        }
+
+        // Drop order matters: wgpu surface before layer surface
+        if let Some(wgpu_surface) = surface.wgpu_surface.take() {
+            drop(wgpu_surface);
+        }
+        drop(surface.layer);
+
+        // Restore cursor when last screensaver deactivates
+        if self.surfaces.is_empty() {
+            std::thread::spawn(|| {
+                let rt = tokio::runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .build()
+                    .unwrap();
+                if let Err(e) = rt.block_on(ipc::show_cursor()) {
+                    warn!("Failed to show cursor: {}", e);
+                }
+            });
+        }
+        true
+    }
+
+    /// Re-read and rebuild every active screensaver's shader in place
+    /// (`--preview` hot-reload). The time uniform keeps running; a shader
+    /// that fails to load or build is reported and the last good version
+    /// keeps rendering.
+    pub fn reload_shaders(&mut self) {
+        for (name, surface) in self.surfaces.iter_mut() {
+            let saver_name = &surface.screensaver.name;
+            let (mut saver, source) = match load_saver(saver_name) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    error!("{}", e);
+                    warn!("Keeping the last good version of '{}' on {}", saver_name, name);
+                    continue;
+                }
+            };
+
+            let options =
+                saver_options(saver.as_ref(), &surface.screensaver.options, self.message.as_deref());
+            let bindings = self.gpu.create_bindings(&surface.uniform_buffer, saver.as_ref(), &options);
+            if let Some(format) = surface.format {
+                match self.gpu.try_create_pipelines(saver_name, &source, format, &bindings) {
+                    Ok(pipeline) => surface.pipeline = Some(pipeline),
+                    Err(e) => {
+                        error!("Screensaver '{}' failed to build: {}", saver_name, e);
+                        warn!("Keeping the last good version of '{}' on {}", saver_name, name);
+                        continue;
                     }
-                });
+                }
+            }
+
+            // Pick up where the old instance was
+            if surface.width > 0 {
+                saver.init(surface.width, surface.height, &options);
             }
+            saver.update(surface.start_time.elapsed().as_secs_f32());
+
+            // Passes start over: the new shader may not read the old state
+            // the same way
+            if surface.width > 0 {
+                surface.textures = self.gpu.create_textures(&bindings, surface.width, surface.height);
+            }
+            surface.bindings = bindings;
+            surface.saver = saver;
+            surface.shader_source = source;
+            info!("Reloaded '{}' on {}", saver_name, name);
+        }
+    }
+
+    /// If the GPU device was lost, create a new one and restart every
+    /// active screensaver on it. Their buffers, bind groups and wgpu surfaces
+    /// belong to the old device, so the surfaces are rebuilt from scratch.
+    fn recover_device(&mut self) {
+        if !self.gpu.device_lost.load(Ordering::SeqCst) {
+            return;
+        }
+        warn!("Recreating GPU device and {} active surface(s)", self.surfaces.len());
+
+        let gpu = match GpuContext::new(&self.conn) {
+            Ok(gpu) => gpu,
+            Err(e) => {
+                // Stays flagged: the next start tries again
+                let names: Vec<String> = self.surfaces.keys().cloned().collect();
+                for name in names {
+                    self.fail_screensaver(&name, format!("failed to recreate GPU device: {}", e));
+                }
+                return;
+            }
+        };
+
+        let mut active = Vec::new();
+        for (name, mut surface) in std::mem::take(&mut self.surfaces) {
+            if let Some(token) = surface.frame_timer.take()
+                && let Some(ref handle) = self.loop_handle
+            {
+                handle.remove(token);
+            }
+            // wgpu surface before layer surface, both before the old device
+            drop(surface.wgpu_surface.take());
+            drop(surface.layer);
+            active.push((name, surface.screensaver));
+        }
+        self.gpu = gpu;
+
+        let qh = self.qh.clone();
+        for (name, screensaver) in active {
+            self.start_screensaver(&name, &screensaver, &qh);
         }
     }
 
@@ -662,21 +1827,27 @@ impl WaylandState {
     fn stop_all(&mut self) {
         let names: Vec<String> = self.surfaces.keys().cloned().collect();
         for name in names {
-            self.stop_screensaver(&name);
+            self.stop_screensaver(&name, StopReason::Requested);
         }
     }
 
     /// Start screensavers on ALL monitors (session-wide idle)
-    fn start_all(&mut self, screensaver_name: &str) {
+    fn start_all(&mut self, screensaver: &ScreensaverConfig) {
         let names: Vec<String> = self.output_map.values().cloned().collect();
         let qh = self.qh.clone();
         for name in names {
-            self.start_screensaver(&name, screensaver_name, &qh);
+            self.start_screensaver(&name, screensaver, &qh);
         }
     }
 
     /// Render a frame for a specific monitor
     fn render_frame(&mut self, output_name: &str) -> bool {
+        if self.gpu.device_lost.load(Ordering::SeqCst) {
+            // The rebuilt surfaces start their own frame chains
+            self.recover_device();
+            return false;
+        }
+
         let surface = match self.surfaces.get_mut(output_name) {
             Some(s) if s.configured => s,
             _ => return false,
@@ -687,16 +1858,67 @@ impl WaylandState {
             return false;
         }
 
-        let elapsed = surface.start_time.elapsed().as_secs_f32();
+        let dt = surface.last_frame.map_or(0.0, |t| t.elapsed().as_secs_f32());
+        surface.saver.update(dt);
+        let replaced = surface.saver.take_images();
+        if !replaced.is_empty() {
+            self.gpu
+                .replace_images(&mut surface.bindings, surface.textures.as_mut(), replaced);
+        }
         let uniforms = Uniforms {
-            time: elapsed,
-            _pad: 0.0,
+            time: surface.saver.time(),
+            opacity: surface.screensaver.opacity,
             resolution: [surface.width as f32, surface.height as f32],
+            frame: surface.frame_count,
+            _pad: [0; 3],
+            date: local_date(),
         };
 
         self.gpu
             .queue
             .write_buffer(&surface.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+        self.gpu
+            .upload(&mut surface.bindings, &surface.uniform_buffer, surface.saver.as_ref());
+        let resolution = [surface.width as f32, surface.height as f32];
+        match surface.saver.text() {
+            Some(style) => {
+                // A new atlas when the font, size or alignment changed
+                let current = surface.text.as_ref().is_some_and(|overlay| overlay.draws(&style));
+                if !current && let Some(format) = surface.format {
+                    surface.text =
+                        Some(self.gpu.create_text_overlay(style.font, style.size, style.align, format));
+                }
+                let [r, g, b, a] = style.color;
+                if let Some(ref mut overlay) = surface.text {
+                    self.gpu.set_text(overlay, style.text);
+                    let uniforms = TextUniforms {
+                        color: [r, g, b, a * surface.screensaver.opacity],
+                        resolution,
+                        offset: surface.saver.place_text(overlay.size),
+                    };
+                    self.gpu
+                        .queue
+                        .write_buffer(&overlay.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+                }
+            }
+            None => surface.text = None,
+        }
+        if let Some(ref mut overlay) = surface.clock {
+            let config = &surface.screensaver.clock;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default();
+            self.gpu
+                .set_text(overlay, &clock::format_time(&config.format, now.as_secs() as i64));
+            let uniforms = TextUniforms {
+                color: [config.color[0], config.color[1], config.color[2], config.opacity],
+                resolution,
+                offset: clock::position(config, overlay.size, resolution, now.as_secs_f64()),
+            };
+            self.gpu
+                .queue
+                .write_buffer(&overlay.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+        }
 
         let frame = match surface.wgpu_surface.as_ref() {
             Some(wgpu_surface) => match wgpu_surface.get_current_texture() {
@@ -706,22 +1928,12 @@ impl WaylandState {
                     return true;
                 }
                 Err(e) => {
-                    warn!(
-                        "Invalidating render resources for {} after surface error: {}",
-                        output_name, e
-                    );
-                    surface.wgpu_surface = None;
-                    surface.pipeline = None;
-                    surface.format = None;
-                    surface.configured = false;
+                    self.fail_screensaver(output_name, format!("surface error: {}", e));
                     return false;
                 }
             },
             None => {
-                warn!("Render resources missing for {}, stopping frame loop", output_name);
-                surface.pipeline = None;
-                surface.format = None;
-                surface.configured = false;
+                self.fail_screensaver(output_name, "render resources missing".to_string());
                 return false;
             }
         };
@@ -736,6 +1948,31 @@ impl WaylandState {
                 label: Some("screensaver_encoder"),
             });
 
+        let pipelines = surface.pipeline.as_ref().expect("pipeline must exist when configured");
+        let parity = (surface.frame_count % 2) as usize;
+        if let Some(ref textures) = surface.textures {
+            for (i, pipeline) in pipelines.passes.iter().enumerate() {
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("offscreen_pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &textures.views[i][parity],
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &surface.bindings.bind_group, &[]);
+                pass.set_bind_group(passes::PASS_GROUP, &textures.bind_groups[parity][i], &[]);
+                self.gpu.draw_quad(&mut pass);
+            }
+        }
+
         {
             let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("screensaver_pass"),
@@ -743,7 +1980,7 @@ impl WaylandState {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: wgpu::LoadOp::Clear(clear_color(surface.screensaver.opacity)),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -752,18 +1989,90 @@ impl WaylandState {
                 occlusion_query_set: None,
             });
 
-            pass.set_pipeline(surface.pipeline.as_ref().expect("pipeline must exist when configured"));
-            pass.set_bind_group(0, &surface.bind_group, &[]);
-            pass.set_vertex_buffer(0, self.gpu.vertex_buffer.slice(..));
-            pass.set_index_buffer(self.gpu.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            pass.draw_indexed(0..6, 0, 0..1);
+            pass.set_pipeline(&pipelines.main);
+            pass.set_bind_group(0, &surface.bindings.bind_group, &[]);
+            if let Some(ref textures) = surface.textures {
+                let stage = pipelines.passes.len();
+                pass.set_bind_group(passes::PASS_GROUP, &textures.bind_groups[parity][stage], &[]);
+            }
+            let opacity = surface.screensaver.opacity as f64;
+            pass.set_blend_constant(wgpu::Color {
+                r: opacity,
+                g: opacity,
+                b: opacity,
+                a: opacity,
+            });
+            self.gpu.draw_quad(&mut pass);
+            if let Some(ref overlay) = surface.text {
+                self.gpu.draw_text(&mut pass, overlay);
+            }
+            if let Some(ref overlay) = surface.clock {
+                self.gpu.draw_text(&mut pass, overlay);
+            }
         }
 
         self.gpu.queue.submit(Some(encoder.finish()));
         frame.present();
+        surface.last_frame = Some(Instant::now());
+        surface.frame_count = surface.frame_count.wrapping_add(1);
+
+        if !surface.reported_started {
+            surface.reported_started = true;
+            self.publish(RendererEvent::Started {
+                monitor: output_name.to_string(),
+            });
+        }
         true
     }
 
+    /// Arrange for the next frame at the monitor's configured fps.
+    ///
+    /// The compositor sends frame callbacks at the display's refresh rate, so
+    /// when the next frame isn't due yet we wait on a calloop timer before
+    /// asking for one. `fps = 0` renders once and stops.
+    fn schedule_frame(&mut self, output_name: &str, qh: &QueueHandle<Self>) {
+        let Some(surface) = self.surfaces.get(output_name) else {
+            return;
+        };
+        if surface.frame_timer.is_some() {
+            return;
+        }
+        let since_last = surface.last_frame.map_or(Duration::MAX, |t| t.elapsed());
+        let delay = match frame_delay(frame_rate(&surface.screensaver), since_last) {
+            Some(delay) => delay,
+            None => return,
+        };
+        let Some(ref handle) = self.loop_handle else {
+            self.request_frame(output_name, qh);
+            return;
+        };
+        if delay.is_zero() {
+            self.request_frame(output_name, qh);
+            return;
+        }
+
+        let name = output_name.to_string();
+        let token = handle.insert_source(Timer::from_duration(delay), move |_, _, state| {
+            if let Some(surface) = state.surfaces.get_mut(&name) {
+                surface.frame_timer = None;
+                let qh = state.qh.clone();
+                state.request_frame(&name, &qh);
+            }
+            TimeoutAction::Drop
+        });
+        match token {
+            Ok(token) => {
+                if let Some(surface) = self.surfaces.get_mut(output_name) {
+                    surface.frame_timer = Some(token);
+                }
+            }
+            Err(e) => {
+                warn!("Failed to schedule frame timer for {}: {}", output_name, e);
+                self.request_frame(output_name, qh);
+            }
+        }
+    }
+
     /// Request the next frame callback for a monitor
     fn request_frame(&self, output_name: &str, qh: &QueueHandle<Self>) {
         if let Some(surface) = self.surfaces.get(output_name)
@@ -819,7 +2128,7 @@ impl CompositorHandler for WaylandState {
         if let Some(name) = output_name
             && self.render_frame(&name)
         {
-            self.request_frame(&name, qh);
+            self.schedule_frame(&name, qh);
         }
     }
 
@@ -880,7 +2189,7 @@ impl OutputHandler for WaylandState {
     ) {
         if let Some(name) = self.output_map.remove(&output) {
             info!("Output removed: {}", name);
-            self.stop_screensaver(&name);
+            self.stop_screensaver(&name, StopReason::OutputRemoved);
         }
     }
 }
@@ -896,7 +2205,7 @@ impl LayerShellHandler for WaylandState {
 
         if let Some(name) = name {
             info!("Layer surface closed for {}", name);
-            self.stop_screensaver(&name);
+            self.stop_screensaver(&name, StopReason::Closed);
         }
     }
 
@@ -939,7 +2248,7 @@ impl LayerShellHandler for WaylandState {
             match create_wgpu_surface(&self.conn, layer, &self.gpu.instance) {
                 Ok(surface) => Some(surface),
                 Err(e) => {
-                    warn!("Failed to create wgpu surface for {}: {}", name, e);
+                    self.fail_screensaver(&name, format!("failed to create wgpu surface: {}", e));
                     return;
                 }
             }
@@ -960,8 +2269,7 @@ impl LayerShellHandler for WaylandState {
         let caps = wgpu_surface.get_capabilities(&self.gpu.adapter);
 
         if caps.formats.is_empty() {
-            warn!("No supported surface formats reported for {}", name);
-            surface.configured = false;
+            self.fail_screensaver(&name, "no supported surface formats".to_string());
             return;
         }
 
@@ -994,33 +2302,76 @@ impl LayerShellHandler for WaylandState {
             },
         );
 
-        // Rebuild pipeline if format changed
-        if surface.pipeline.is_none() || surface.format != Some(format) {
-            let shader_source = build_shader_source(&surface.screensaver_name);
-            let shader = self
-                .gpu
-                .device
-                .create_shader_module(wgpu::ShaderModuleDescriptor {
-                    label: Some(&surface.screensaver_name),
-                    source: wgpu::ShaderSource::Wgsl(shader_source.into()),
-                });
-            surface.pipeline = Some(Self::create_pipeline(
-                &self.gpu.device,
-                &self.gpu.bind_group_layout,
-                &shader,
-                format,
-            ));
+        // Rebuild pipeline if format changed. If the screensaver's shader
+        // doesn't build, this monitor shows blank instead.
+        let mut degraded = None;
+        let rebuilt = surface.pipeline.is_none() || surface.format != Some(format);
+        if rebuilt {
+            let label = &surface.screensaver.name;
+            let pipeline = match self.gpu.try_create_pipelines(label, &surface.shader_source, format, &surface.bindings) {
+                Ok(pipeline) => pipeline,
+                Err(e) => {
+                    warn!("Screensaver '{}' failed to build on {}: {}", label, name, e);
+                    warn!("Falling back to blank on {}", name);
+                    (surface.saver, surface.shader_source) = fallback_saver();
+                    surface.bindings = self.gpu.create_bindings(
+                        &surface.uniform_buffer,
+                        surface.saver.as_ref(),
+                        &saver_options(
+                            surface.saver.as_ref(),
+                            &surface.screensaver.options,
+                            self.message.as_deref(),
+                        ),
+                    );
+                    match self.gpu.try_create_pipelines("blank", &surface.shader_source, format, &surface.bindings) {
+                        Ok(pipeline) => {
+                            degraded = Some(format!("'{}' failed to build: {}", label, e));
+                            pipeline
+                        }
+                        Err(e) => {
+                            self.fail_screensaver(&name, format!("fallback pipeline failed: {}", e));
+                            return;
+                        }
+                    }
+                }
+            };
+            surface.pipeline = Some(pipeline);
             surface.format = Some(format);
+
+            let clock = &surface.screensaver.clock;
+            surface.clock = clock.enabled.then(|| {
+                self.gpu.create_text_overlay(
+                    clock.font.as_deref(),
+                    clock.size,
+                    clock::align(clock.position),
+                    format,
+                )
+            });
+            surface.text = None;
         }
 
+        let resized = (width, height) != (surface.width, surface.height);
+        if degraded.is_some() || resized {
+            surface.init_saver(width, height, self.message.as_deref());
+        }
+        if rebuilt || resized {
+            surface.textures = self.gpu.create_textures(&surface.bindings, width, height);
+        }
         surface.width = width;
         surface.height = height;
         surface.configured = true;
 
+        if let Some(error) = degraded {
+            self.publish(RendererEvent::Degraded {
+                monitor: name.clone(),
+                error,
+            });
+        }
+
         // Render first frame and start the frame callback chain
         let name_clone = name.clone();
         if self.render_frame(&name_clone) {
-            self.request_frame(&name_clone, qh);
+            self.schedule_frame(&name_clone, qh);
         }
     }
 }
@@ -1038,6 +2389,7 @@ impl SeatHandler for WaylandState {
     ) {
         // Create idle notification when we get a seat and have the notifier
         if self.idle_notification.is_none()
+            && self.session_idle_config.enabled
             && let Some(ref notifier) = self.idle_notifier
         {
             let timeout_ms = (self.session_idle_config.timeout_secs * 1000) as u32;
@@ -1150,6 +2502,36 @@ impl ProvidesRegistryState for WaylandState {
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Clear colour for a frame: opaque black, or fully transparent when the
+/// saver is translucent so the desktop shows through
+fn clear_color(opacity: f32) -> wgpu::Color {
+    if opacity < 1.0 {
+        wgpu::Color::TRANSPARENT
+    } else {
+        wgpu::Color::BLACK
+    }
+}
+
+/// Frames per second a surface renders at: its configured fps, except that
+/// a clock has to keep ticking over a still screensaver
+fn frame_rate(screensaver: &ScreensaverConfig) -> u32 {
+    match screensaver.fps {
+        0 if screensaver.clock.enabled => 1,
+        fps => fps,
+    }
+}
+
+/// How long to wait before requesting the next frame callback, given the
+/// target fps and the time since the last frame. `None` means don't render
+/// again (`fps = 0`).
+fn frame_delay(fps: u32, since_last: Duration) -> Option<Duration> {
+    if fps == 0 {
+        return None;
+    }
+    let interval = Duration::from_secs(1) / fps;
+    Some(interval.saturating_sub(since_last))
+}
+
 /// Extract a usable name from OutputInfo
 fn output_name_from_info(info: &OutputInfo) -> String {
     info.name
@@ -1200,8 +2582,12 @@ mod tests {
     #[test]
     fn shader_sources_compile() {
         // Verify all shader sources can be loaded and concatenated
-        for name in &["blank", "matrix", "plasmula", "starfield"] {
-            let source = build_shader_source(name);
+        for builtin in screensavers::builtins() {
+            let name = builtin.name();
+            let (saver, source) = build_saver(name);
+            assert_eq!(saver.name(), name);
+            assert!(source.contains("var<uniform> params: Params"), "{}", name);
+            assert_eq!(saver.params().len() % 16, 0, "{}", name);
             assert!(
                 source.contains("vs_main"),
                 "missing vertex entry in {}",
@@ -1212,20 +2598,51 @@ mod tests {
                 "missing fragment entry in {}",
                 name
             );
+            assert!(!source.contains("#import"), "{}", name);
         }
     }
 
+    #[test]
+    fn text_shader_is_valid() {
+        let module = naga::front::wgsl::parse_str(TEXT_SHADER).unwrap();
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+
+        let mut atlas = text::GlyphAtlas::new(text::fallback_font(), 16.0);
+        let vertices = text_vertices(&atlas.layout("12", text::Align::Left));
+        assert_eq!(vertices.len(), 12);
+        assert_eq!(std::mem::size_of::<TextUniforms>() % 16, 0);
+    }
+
     #[test]
     fn unknown_shader_falls_back() {
-        let source = build_shader_source("nonexistent");
+        let (saver, source) = build_saver("nonexistent");
         // Should fall back to blank
+        assert_eq!(saver.name(), "blank");
         assert!(source.contains("fs_main"));
     }
 
+    #[test]
+    fn passes_read_this_frame_from_earlier_stages_only() {
+        // Frame with parity 0 writes texture 0 of every pass
+        assert_eq!(pass_texture(0, 0, 0), 1);
+        assert_eq!(pass_texture(0, 1, 0), 1);
+        assert_eq!(pass_texture(1, 0, 0), 0);
+        assert_eq!(pass_texture(1, 1, 1), 0);
+        // fs_main (stage 2 with two passes) sees this frame's results
+        assert_eq!(pass_texture(2, 0, 1), 1);
+        assert_eq!(pass_texture(2, 1, 1), 1);
+    }
+
     #[test]
     fn uniforms_layout() {
         // Verify uniform struct is correctly sized for GPU alignment
-        assert_eq!(std::mem::size_of::<Uniforms>(), 16); // 4 + 4 + 8 = 16 bytes
+        // 4 + 4 + 8 + 4 (+ 12 padding, date is 16-byte aligned) + 16 = 48 bytes
+        assert_eq!(std::mem::size_of::<Uniforms>(), 48);
     }
 
     #[test]
@@ -1243,10 +2660,16 @@ mod tests {
         let cmds = [
             RendererCommand::Start {
                 monitor: "DP-1".into(),
-                screensaver: "matrix".into(),
+                screensaver: ScreensaverConfig {
+                    name: "matrix".into(),
+                    ..Default::default()
+                },
             },
             RendererCommand::StartAll {
-                screensaver: "matrix".into(),
+                screensaver: ScreensaverConfig {
+                    name: "matrix".into(),
+                    ..Default::default()
+                },
             },
             RendererCommand::Stop {
                 monitor: "DP-1".into(),
@@ -1255,9 +2678,35 @@ mod tests {
             RendererCommand::MonitorRemoved {
                 monitor: "DP-1".into(),
             },
+            RendererCommand::SetSessionIdle {
+                enabled: true,
+                timeout_secs: 600,
+            },
+            RendererCommand::SetMessage {
+                text: Some("Back at 14:30".into()),
+            },
             RendererCommand::Shutdown,
         ];
-        assert_eq!(cmds.len(), 6);
+        assert_eq!(cmds.len(), 8);
+    }
+
+    #[test]
+    fn away_message_replaces_the_configured_one() {
+        let saver = screensavers::message::Message::new();
+        let options = HashMap::from([
+            ("message".to_string(), toml::Value::String("Lunch".into())),
+            ("speed".to_string(), toml::Value::Float(40.0)),
+        ]);
+        assert!(matches!(saver_options(&saver, &options, None), Cow::Borrowed(_)));
+        let live = saver_options(&saver, &options, Some("Back at 14:30"));
+        assert_eq!(live["message"].as_str(), Some("Back at 14:30"));
+        assert_eq!(live["speed"], options["speed"]);
+
+        // Savers without a string `message` parameter keep their options
+        let matrix = screensavers::matrix::Matrix::new();
+        assert!(!takes_message(&matrix));
+        let live = saver_options(&matrix, &options, Some("Back at 14:30"));
+        assert!(matches!(live, Cow::Borrowed(_)));
     }
 
     #[test]
@@ -1298,17 +2747,41 @@ mod tests {
     #[test]
     fn start_all_command_clone() {
         let cmd = RendererCommand::StartAll {
-            screensaver: "starfield".into(),
+            screensaver: ScreensaverConfig {
+                name: "starfield".into(),
+                fps: 60,
+                ..Default::default()
+            },
         };
         let cloned = cmd.clone();
         match cloned {
             RendererCommand::StartAll { screensaver } => {
-                assert_eq!(screensaver, "starfield");
+                assert_eq!(screensaver.name, "starfield");
+                assert_eq!(screensaver.fps, 60);
             }
             _ => panic!("Expected StartAll"),
         }
     }
 
+    #[test]
+    fn translucent_savers_clear_to_transparent() {
+        assert_eq!(clear_color(1.0), wgpu::Color::BLACK);
+        assert_eq!(clear_color(0.3), wgpu::Color::TRANSPARENT);
+        assert_eq!(clear_color(0.0), wgpu::Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn frame_delay_paces_to_fps() {
+        assert_eq!(frame_delay(0, Duration::ZERO), None);
+        assert_eq!(
+            frame_delay(10, Duration::from_millis(30)),
+            Some(Duration::from_millis(70))
+        );
+        // Already late: ask for a frame straight away
+        assert_eq!(frame_delay(30, Duration::from_millis(50)), Some(Duration::ZERO));
+        assert_eq!(frame_delay(30, Duration::MAX), Some(Duration::ZERO));
+    }
+
     #[test]
     fn zero_sized_configure_is_ignored() {
         assert_eq!(validated_configure_size((0, 1080)), None);
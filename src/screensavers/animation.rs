@@ -0,0 +1,548 @@
+use super::params::ParamBlock;
+use super::{images, AnimationClock, Screensaver};
+use image::codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder};
+use image::{AnimationDecoder, Frames, ImageFormat};
+use log::{debug, warn};
+use std::collections::{HashMap, VecDeque};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+
+pub const SHADER: &str = include_str!("../../screensavers/shaders/animation.wgsl");
+
+/// Frame delays shorter than this are shown for `DEFAULT_DELAY`, as browsers
+/// do; plenty of GIFs say 0 and expect it
+const MIN_DELAY: f32 = 0.02;
+const DEFAULT_DELAY: f32 = 0.1;
+
+/// Frames decoded ahead of the one showing, when streaming
+const READ_AHEAD: usize = 8;
+
+/// Most frames skipped in one update when playback falls behind
+const MAX_CATCH_UP: usize = 64;
+
+/// How frames are scaled to the screen (`u32` for the shader)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scale {
+    /// Whole frame, letterboxed
+    Fit = 0,
+    /// Whole screen, cropped
+    Fill = 1,
+    /// Whole frame on the whole screen, out of proportion
+    Stretch = 2,
+    /// Largest whole multiple that fits, letterboxed
+    Integer = 3,
+}
+
+impl Scale {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "fit" => Some(Self::Fit),
+            "fill" => Some(Self::Fill),
+            "stretch" => Some(Self::Stretch),
+            "integer" => Some(Self::Integer),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded frame and how long it shows
+struct Frame {
+    image: Arc<image::RgbaImage>,
+    delay: f32,
+}
+
+impl Frame {
+    fn bytes(&self) -> usize {
+        self.image.as_raw().len()
+    }
+}
+
+/// Everything the loader thread depends on
+#[derive(Debug, Clone, PartialEq)]
+struct LoaderConfig {
+    path: PathBuf,
+    /// Output size, and how frames are fitted to it
+    size: (u32, u32),
+    scale: Scale,
+    /// Bytes of frames to keep in memory
+    memory: usize,
+}
+
+/// What the loader thread sends back
+enum Loaded {
+    Frame(Frame),
+    /// The last frame went out. The loader starts over if the animation is
+    /// too big to keep (`again`), and is done otherwise.
+    End { again: bool },
+    Failed(String),
+}
+
+/// Animation screensaver - frames are decoded on a loader thread, fitted to
+/// the output, and handed to the renderer through `take_images` as they come
+/// up. An animation that fits in the memory budget is decoded once and
+/// loops from memory; a bigger one is decoded again on every loop, a few
+/// frames ahead of the one showing.
+pub struct Animation {
+    params: ParamBlock,
+    clock: AnimationClock,
+    /// What the loader was started for; a change restarts it
+    loader_config: Option<LoaderConfig>,
+    results: Option<mpsc::Receiver<Loaded>>,
+    /// Frames received and still kept: the whole animation once `complete`,
+    /// else from the one showing on
+    frames: VecDeque<Frame>,
+    /// Bytes in `frames`
+    bytes: usize,
+    /// Index in `frames` of the frame showing, if any
+    current: Option<usize>,
+    /// Seconds the frame showing has been up
+    shown_for: f32,
+    /// Every frame is in `frames`, and they loop from there
+    complete: bool,
+    /// Too big to keep: frames are dropped once shown
+    streaming: bool,
+    /// Frame for the renderer
+    taken: Option<Arc<image::RgbaImage>>,
+    /// (scale, loaded, 0, 0) as the shader reads it
+    gpu: [u32; 4],
+}
+
+impl Animation {
+    pub fn new() -> Self {
+        Self {
+            params: super::header_params(SHADER),
+            clock: AnimationClock::new(),
+            loader_config: None,
+            results: None,
+            frames: VecDeque::new(),
+            bytes: 0,
+            current: None,
+            shown_for: 0.0,
+            complete: false,
+            streaming: false,
+            taken: None,
+            gpu: [0; 4],
+        }
+    }
+
+    /// (Re)start the loader thread. The frame showing stays up until the
+    /// new loader's first one arrives.
+    fn start_loader(&mut self, config: LoaderConfig) {
+        let (tx, rx) = mpsc::sync_channel(READ_AHEAD);
+        let thread_config = config.clone();
+        let spawned = std::thread::Builder::new()
+            .name("animation".to_string())
+            .spawn(move || run_loader(thread_config, tx));
+        if let Err(e) = spawned {
+            warn!("Screensaver 'animation': failed to start the loader: {}", e);
+        }
+        self.results = Some(rx);
+        self.loader_config = Some(config);
+        self.frames.clear();
+        self.bytes = 0;
+        self.current = None;
+        self.shown_for = 0.0;
+        self.complete = false;
+        self.streaming = false;
+    }
+
+    fn memory(&self) -> usize {
+        self.loader_config.as_ref().map_or(usize::MAX, |config| config.memory)
+    }
+
+    /// Take in what the loader has decoded: everything while the animation
+    /// may still fit in memory, else only until a frame is waiting
+    fn poll_loader(&mut self) {
+        while let Some(results) = self.results.as_ref() {
+            let waiting = self.frames.len() > self.current.map_or(0, |i| i + 1);
+            if self.streaming && waiting {
+                return;
+            }
+            let Ok(loaded) = results.try_recv() else {
+                return;
+            };
+            match loaded {
+                Loaded::Frame(frame) => {
+                    self.bytes += frame.bytes();
+                    self.frames.push_back(frame);
+                    if !self.streaming && self.bytes > self.memory() {
+                        debug!("Animation: over {} bytes, streaming", self.memory());
+                        self.streaming = true;
+                        self.drop_shown();
+                    }
+                }
+                Loaded::End { again } => {
+                    if !again && !self.streaming {
+                        self.complete = true;
+                        self.results = None;
+                    }
+                }
+                Loaded::Failed(e) => {
+                    warn!("Screensaver 'animation': {}", e);
+                    self.results = None;
+                }
+            }
+        }
+    }
+
+    /// Forget the frames before the one showing
+    fn drop_shown(&mut self) {
+        let Some(current) = self.current else {
+            return;
+        };
+        for frame in self.frames.drain(..current) {
+            self.bytes -= frame.bytes();
+        }
+        self.current = Some(0);
+    }
+
+    /// Move on to the next frame, if it's there
+    fn advance(&mut self) -> bool {
+        let Some(current) = self.current else {
+            return false;
+        };
+        let next = if current + 1 < self.frames.len() {
+            current + 1
+        } else if self.complete && current > 0 {
+            0
+        } else {
+            return false;
+        };
+        self.current = Some(next);
+        if self.streaming {
+            self.drop_shown();
+        }
+        true
+    }
+}
+
+impl Screensaver for Animation {
+    fn name(&self) -> &str {
+        "animation"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        SHADER
+    }
+
+    fn init(&mut self, width: u32, height: u32, options: &HashMap<String, toml::Value>) {
+        super::configure_params("animation", &mut self.params, options);
+        self.clock.configure(&self.params);
+
+        let name = self.params.string("scale", "fit");
+        let scale = Scale::parse(name).unwrap_or_else(|| {
+            warn!("Screensaver 'animation': unknown scale '{}', using fit", name);
+            Scale::Fit
+        });
+        self.gpu[0] = scale as u32;
+
+        let file = self.params.string("file", "");
+        if file.is_empty() {
+            if self.loader_config.is_none() {
+                warn!("Screensaver 'animation': no file set");
+            }
+            return;
+        }
+        let config = LoaderConfig {
+            path: images::resolve(file),
+            size: (width, height),
+            scale,
+            memory: (self.params.number("memory", 256.0).max(1.0) * 1024.0 * 1024.0) as usize,
+        };
+        if self.loader_config.as_ref() != Some(&config) {
+            self.start_loader(config);
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        let dt = self.clock.tick(dt);
+        self.poll_loader();
+
+        let mut changed = false;
+        if self.current.is_none() && !self.frames.is_empty() {
+            self.current = Some(0);
+            self.shown_for = 0.0;
+            changed = true;
+        } else {
+            self.shown_for += dt;
+            for _ in 0..MAX_CATCH_UP {
+                let Some(current) = self.current else {
+                    break;
+                };
+                let delay = self.frames[current].delay;
+                if self.shown_for < delay {
+                    break;
+                }
+                if !self.advance() {
+                    // Show the next one as soon as it's decoded
+                    self.poll_loader();
+                    if !self.advance() {
+                        self.shown_for = delay;
+                        break;
+                    }
+                }
+                changed = true;
+                self.shown_for -= delay;
+            }
+            // Don't race through frames to make up for a long stall
+            if let Some(current) = self.current {
+                self.shown_for = self.shown_for.min(self.frames[current].delay);
+            }
+        }
+
+        if let Some(current) = self.current
+            && changed
+        {
+            self.taken = Some(Arc::clone(&self.frames[current].image));
+            self.gpu[1] = 1;
+        }
+    }
+
+    fn time(&self) -> f32 {
+        self.clock.time()
+    }
+
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
+    }
+
+    fn storage(&self) -> Vec<&[u8]> {
+        vec![bytemuck::cast_slice(&self.gpu)]
+    }
+
+    fn take_images(&mut self) -> Vec<(usize, Arc<image::RgbaImage>)> {
+        self.taken.take().map(|image| (0, image)).into_iter().collect()
+    }
+}
+
+/// Seconds to show a frame for
+fn frame_delay(delay: image::Delay) -> f32 {
+    let (numer, denom) = delay.numer_denom_ms();
+    let secs = numer as f32 / denom.max(1) as f32 / 1000.0;
+    if secs < MIN_DELAY { DEFAULT_DELAY } else { secs }
+}
+
+/// Size to decode frames of `image` size at for an output of `output` size:
+/// no bigger than the output shows them, and never scaled up
+fn frame_size(image: (u32, u32), output: (u32, u32), scale: Scale) -> (u32, u32) {
+    let (width, height) = (image.0.max(1) as f32, image.1.max(1) as f32);
+    let fit = [output.0 as f32 / width, output.1 as f32 / height];
+    let factors = match scale {
+        Scale::Fit | Scale::Integer => [fit[0].min(fit[1]); 2],
+        Scale::Fill => [fit[0].max(fit[1]); 2],
+        Scale::Stretch => fit,
+    };
+    let size = |side: f32, factor: f32| ((side * factor.min(1.0)).round() as u32).max(1);
+    (size(width, factors[0]), size(height, factors[1]))
+}
+
+/// Frames of an animated GIF, APNG or WebP file. Anything else `image`
+/// reads is a single frame.
+fn open(path: &Path) -> Result<Frames<'static>, String> {
+    let fail = |e: image::ImageError| format!("Failed to load {}: {}", path.display(), e);
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let reader = BufReader::new(file);
+    let format = image::ImageReader::new(reader)
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let kind = format.format();
+    let reader = format.into_inner();
+    match kind {
+        Some(ImageFormat::Gif) => return Ok(GifDecoder::new(reader).map_err(fail)?.into_frames()),
+        Some(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(reader).map_err(fail)?;
+            if decoder.is_apng().map_err(fail)? {
+                return Ok(decoder.apng().map_err(fail)?.into_frames());
+            }
+        }
+        Some(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(reader).map_err(fail)?;
+            if decoder.has_animation() {
+                return Ok(decoder.into_frames());
+            }
+        }
+        _ => {}
+    }
+    let still = image::open(path).map_err(fail)?.into_rgba8();
+    Ok(Frames::new(Box::new(std::iter::once(Ok(image::Frame::new(still))))))
+}
+
+/// Loader thread: decode the file frame by frame, over and over if it's
+/// too big to keep, until the screensaver goes away
+fn run_loader(config: LoaderConfig, results: mpsc::SyncSender<Loaded>) {
+    let mut first = true;
+    loop {
+        let frames = match open(&config.path) {
+            Ok(frames) => frames,
+            Err(e) => {
+                let _ = results.send(Loaded::Failed(e));
+                return;
+            }
+        };
+        let mut count = 0;
+        let mut bytes = 0;
+        for frame in frames {
+            let frame = match frame {
+                Ok(frame) => frame,
+                // Play what's there of a truncated file
+                Err(e) => {
+                    if first && count > 0 {
+                        warn!("Screensaver 'animation': {}: {}", config.path.display(), e);
+                    }
+                    if count == 0 {
+                        let e = format!("Failed to decode {}: {}", config.path.display(), e);
+                        let _ = results.send(Loaded::Failed(e));
+                        return;
+                    }
+                    break;
+                }
+            };
+            let delay = frame_delay(frame.delay());
+            let image = frame.into_buffer();
+            let (width, height) = frame_size(image.dimensions(), config.size, config.scale);
+            let image = if (width, height) == image.dimensions() {
+                image
+            } else {
+                image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle)
+            };
+            let frame = Frame { image: Arc::new(image), delay };
+            bytes += frame.bytes();
+            count += 1;
+            if results.send(Loaded::Frame(frame)).is_err() {
+                return;
+            }
+        }
+        if first {
+            debug!("Animation: {} frames in {}", count, config.path.display());
+        }
+        let again = bytes > config.memory;
+        if results.send(Loaded::End { again }).is_err() || !again {
+            return;
+        }
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+
+    /// A GIF of `count` 40x20 frames, each its own shade of grey, showing
+    /// for 50, 100, 150, ... ms
+    fn write_gif(path: &Path, count: u8) {
+        let file = std::fs::File::create(path).unwrap();
+        let mut encoder = GifEncoder::new(file);
+        for i in 0..count {
+            let image = image::RgbaImage::from_pixel(40, 20, image::Rgba([i * 40, i * 40, i * 40, 255]));
+            let delay = image::Delay::from_numer_denom_ms(50 * (u32::from(i) + 1), 1);
+            encoder
+                .encode_frame(image::Frame::from_parts(image, 0, 0, delay))
+                .unwrap();
+        }
+    }
+
+    /// Update until the loader has sent `frames` frames, or give up
+    fn wait_for(saver: &mut Animation, frames: usize) {
+        for _ in 0..500 {
+            saver.update(0.0);
+            if saver.frames.len() >= frames || saver.complete {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("loader never sent {} frames", frames);
+    }
+
+    /// Which frame is showing, going by its shade (GIF colours are
+    /// quantized, so only roughly the one written)
+    fn shown(saver: &Animation) -> u32 {
+        index(&saver.frames[saver.current.unwrap()].image)
+    }
+
+    fn index(image: &image::RgbaImage) -> u32 {
+        (u32::from(image.get_pixel(0, 0)[0]) + 20) / 40
+    }
+
+    #[test]
+    fn sizes_frames_for_the_output() {
+        let output = (1920, 1080);
+        assert_eq!(frame_size((400, 300), output, Scale::Fit), (400, 300));
+        assert_eq!(frame_size((3840, 1080), output, Scale::Fit), (1920, 540));
+        assert_eq!(frame_size((3840, 1080), output, Scale::Fill), (3840, 1080));
+        assert_eq!(frame_size((3840, 4320), output, Scale::Fill), (1920, 2160));
+        assert_eq!(frame_size((3840, 400), output, Scale::Stretch), (1920, 400));
+        assert_eq!(frame_delay(image::Delay::from_numer_denom_ms(0, 1)), DEFAULT_DELAY);
+        assert_eq!(frame_delay(image::Delay::from_numer_denom_ms(70, 1)), 0.07);
+    }
+
+    #[test]
+    fn plays_frames_for_their_delays_and_loops_from_memory() {
+        let dir = std::env::temp_dir().join(format!("hyprfresh_test_animation_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("three.gif");
+        write_gif(&path, 3);
+
+        let mut saver = Animation::new();
+        let options = HashMap::from([(
+            "file".to_string(),
+            toml::Value::String(path.display().to_string()),
+        )]);
+        saver.init(1920, 1080, &options);
+        wait_for(&mut saver, 3);
+        assert!(saver.complete && !saver.streaming);
+        assert_eq!(saver.frames.len(), 3);
+        assert_eq!(saver.take_images()[0].1.dimensions(), (40, 20));
+        assert_eq!(saver.gpu, [Scale::Fit as u32, 1, 0, 0]);
+
+        assert_eq!(shown(&saver), 0);
+        saver.update(0.04);
+        assert_eq!(shown(&saver), 0);
+        assert!(saver.take_images().is_empty());
+        saver.update(0.02);
+        assert_eq!(shown(&saver), 1);
+        assert_eq!(index(&saver.take_images()[0].1), 1);
+        saver.update(0.1);
+        assert_eq!(shown(&saver), 2);
+        saver.update(0.15);
+        assert_eq!(shown(&saver), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn streams_animations_over_the_memory_budget() {
+        let dir = std::env::temp_dir().join(format!("hyprfresh_test_animation_stream_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("five.gif");
+        write_gif(&path, 5);
+
+        // Room for two frames
+        let mut saver = Animation::new();
+        saver.start_loader(LoaderConfig {
+            path: path.clone(),
+            size: (1920, 1080),
+            scale: Scale::Fit,
+            memory: 2 * 40 * 20 * 4,
+        });
+        wait_for(&mut saver, 3);
+        assert!(saver.streaming && !saver.complete);
+
+        // Every frame in order, twice round, never holding them all
+        let mut seen = Vec::new();
+        for _ in 0..500 {
+            let frame = shown(&saver);
+            if seen.last() != Some(&frame) {
+                seen.push(frame);
+            }
+            if seen.len() == 10 {
+                break;
+            }
+            assert!(saver.frames.len() <= READ_AHEAD + 3);
+            saver.update(0.05);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        assert_eq!(seen, [0, 1, 2, 3, 4, 0, 1, 2, 3, 4]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
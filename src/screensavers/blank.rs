@@ -1,14 +1,22 @@
+use super::params::ParamBlock;
 use super::Screensaver;
 use std::collections::HashMap;
 
+pub const SHADER: &str = include_str!("../../screensavers/shaders/blank.wgsl");
+
 /// Blank screensaver - just a black screen
 /// Useful for OLED burn-in prevention or minimal power usage
-#[allow(dead_code)]
-pub struct Blank;
+pub struct Blank {
+    params: ParamBlock,
+    time: f32,
+}
 
 impl Blank {
     pub fn new() -> Self {
-        Self
+        Self {
+            params: ParamBlock::new(Vec::new()),
+            time: 0.0,
+        }
     }
 }
 
@@ -17,24 +25,23 @@ impl Screensaver for Blank {
         "blank"
     }
 
-    fn description(&self) -> &str {
-        "Black screen (DPMS-like, minimal power)"
+    fn fragment_shader(&self) -> &str {
+        SHADER
     }
 
     fn init(&mut self, _width: u32, _height: u32, _options: &HashMap<String, toml::Value>) {
         // Nothing to initialize
     }
 
-    fn update(&mut self, _dt: f32) {
-        // Nothing to update
+    fn update(&mut self, dt: f32) {
+        self.time += dt;
     }
 
-    fn fragment_shader(&self) -> &str {
-        r#"
-@fragment
-fn fs_main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
-    return vec4f(0.0, 0.0, 0.0, 1.0);
-}
-"#
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
     }
 }
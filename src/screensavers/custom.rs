@@ -0,0 +1,54 @@
+use super::params::ParamBlock;
+use super::Screensaver;
+use log::info;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A shader from the custom shader directory. Its options are the
+/// parameters declared in its header; `u.time` is plain seconds.
+pub struct CustomShader {
+    name: String,
+    source: String,
+    params: ParamBlock,
+    time: f32,
+}
+
+impl CustomShader {
+    /// Load and validate a custom shader (see `load_custom_shader`)
+    pub fn load(name: &str, path: &Path) -> Result<Self, String> {
+        let source = super::load_custom_shader(path)?;
+        info!("Loaded custom shader '{}' from {}", name, path.display());
+        Ok(Self {
+            name: name.to_string(),
+            params: super::header_params(&source),
+            source,
+            time: 0.0,
+        })
+    }
+}
+
+impl Screensaver for CustomShader {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fragment_shader(&self) -> &str {
+        &self.source
+    }
+
+    fn init(&mut self, _width: u32, _height: u32, options: &HashMap<String, toml::Value>) {
+        super::configure_params(&self.name, &mut self.params, options);
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
+    }
+}
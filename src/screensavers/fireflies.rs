@@ -0,0 +1,167 @@
+use super::params::ParamBlock;
+use super::rng::Rng;
+use super::{AnimationClock, Screensaver};
+use std::collections::HashMap;
+
+pub const SHADER: &str = include_str!("../../screensavers/shaders/fireflies.wgsl");
+
+/// Fastest a firefly flies, in screen heights per second
+const MAX_SPEED: f32 = 0.08;
+
+/// Fireflies screensaver - the flight is simulated here and handed to the
+/// shader in a storage buffer
+pub struct Fireflies {
+    params: ParamBlock,
+    clock: AnimationClock,
+    aspect: f32,
+    flies: Vec<Firefly>,
+    /// (x, y, brightness, size) per firefly, as the shader reads it
+    gpu: Vec<[f32; 4]>,
+    rng: Rng,
+}
+
+struct Firefly {
+    pos: [f32; 2],
+    vel: [f32; 2],
+    /// Blink phase and rate
+    phase: f32,
+    rate: f32,
+    size: f32,
+}
+
+impl Fireflies {
+    pub fn new() -> Self {
+        Self {
+            params: super::header_params(SHADER),
+            clock: AnimationClock::new(),
+            aspect: 16.0 / 9.0,
+            flies: Vec::new(),
+            gpu: vec![[0.0; 4]],
+            rng: Rng::from_time(),
+        }
+    }
+
+    /// Uniform random number in [0, 1)
+    fn random(&mut self) -> f32 {
+        self.rng.next_f32()
+    }
+
+    fn spawn(&mut self) -> Firefly {
+        Firefly {
+            pos: [self.random(), self.random()],
+            vel: [
+                (self.random() - 0.5) * MAX_SPEED,
+                (self.random() - 0.5) * MAX_SPEED,
+            ],
+            phase: self.random() * std::f32::consts::TAU,
+            rate: 0.5 + self.random() * 1.5,
+            size: 0.004 + self.random() * 0.004,
+        }
+    }
+}
+
+impl Screensaver for Fireflies {
+    fn name(&self) -> &str {
+        "fireflies"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        SHADER
+    }
+
+    fn init(&mut self, width: u32, height: u32, options: &HashMap<String, toml::Value>) {
+        super::configure_params("fireflies", &mut self.params, options);
+        self.clock.configure(&self.params);
+        self.aspect = width as f32 / height.max(1) as f32;
+
+        // Keep the ones already flying when resized
+        let count = self.params.number("count", 48.0).max(1.0) as usize;
+        self.flies.truncate(count);
+        while self.flies.len() < count {
+            let fly = self.spawn();
+            self.flies.push(fly);
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        let dt = self.clock.tick(dt);
+
+        for i in 0..self.flies.len() {
+            // Wander: nudge the velocity a little every frame
+            let nudge = [self.random() - 0.5, self.random() - 0.5];
+            let fly = &mut self.flies[i];
+            for (vel, nudge) in fly.vel.iter_mut().zip(nudge) {
+                *vel += nudge * MAX_SPEED * 4.0 * dt;
+            }
+            let speed = (fly.vel[0] * fly.vel[0] + fly.vel[1] * fly.vel[1]).sqrt();
+            if speed > MAX_SPEED {
+                fly.vel = [fly.vel[0] * MAX_SPEED / speed, fly.vel[1] * MAX_SPEED / speed];
+            }
+
+            // Same speed in both directions on screen; wrap at the edges
+            fly.pos[0] = wrap(fly.pos[0] + fly.vel[0] * dt / self.aspect);
+            fly.pos[1] = wrap(fly.pos[1] + fly.vel[1] * dt);
+        }
+
+        self.gpu.clear();
+        self.gpu.extend(self.flies.iter().map(|fly| {
+            let blink = (0.5 + 0.5 * (self.clock.time() * fly.rate + fly.phase).sin()).powi(3);
+            [fly.pos[0], fly.pos[1], blink, fly.size]
+        }));
+        if self.gpu.is_empty() {
+            self.gpu.push([0.0; 4]);
+        }
+    }
+
+    fn time(&self) -> f32 {
+        self.clock.time()
+    }
+
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
+    }
+
+    fn storage(&self) -> Vec<&[u8]> {
+        vec![bytemuck::cast_slice(&self.gpu)]
+    }
+}
+
+/// Wrap a position into [0, 1). `rem_euclid` alone rounds a tiny negative
+/// number up to exactly 1.
+fn wrap(p: f32) -> f32 {
+    let p = p.rem_euclid(1.0);
+    if p >= 1.0 { p - 1.0 } else { p }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulates_the_configured_number_of_flies() {
+        let mut saver = Fireflies::new();
+        let options = HashMap::from([("count".to_string(), toml::Value::Integer(10))]);
+        saver.init(1920, 1080, &options);
+        saver.update(0.1);
+        assert_eq!(saver.storage()[0].len(), 10 * 16);
+
+        for _ in 0..100 {
+            saver.update(0.5);
+        }
+        for [x, y, blink, _] in &saver.gpu {
+            assert!((0.0..1.0).contains(x) && (0.0..1.0).contains(y));
+            assert!((0.0..=1.0).contains(blink));
+        }
+
+        assert_eq!(wrap(-1e-9), 0.0);
+        assert_eq!(wrap(1.25), 0.25);
+
+        // Fewer after a config change, without respawning the rest
+        let first = saver.flies[0].pos;
+        let options = HashMap::from([("count".to_string(), toml::Value::Integer(3))]);
+        saver.init(1920, 1080, &options);
+        saver.update(0.0);
+        assert_eq!(saver.storage()[0].len(), 3 * 16);
+        assert_eq!(saver.flies[0].pos, first);
+    }
+}
@@ -0,0 +1,183 @@
+//! Shadertoy-style GLSL shaders
+//!
+//! A `.glsl` custom shader is a Shadertoy "Image" tab: it defines
+//! `void mainImage(out vec4 fragColor, in vec2 fragCoord)` and may use
+//! `iTime`, `iResolution`, `iMouse`, `iFrame` and `iDate`. We wrap it with a
+//! generated prelude that maps those inputs onto the renderer's uniforms,
+//! translate it to WGSL with naga's GLSL front end, and from then on treat it
+//! like any other fragment shader.
+//!
+//! The metadata header uses the same `// @...` lines as `.wgsl` shaders, with
+//! the same WGSL type names in `@param`; the parameters show up as fields of
+//! a `params` uniform block (`bool` is a `uint`). There is no mouse, so
+//! `iMouse` is always zero, and `iChannel` inputs aren't supported.
+
+use super::header;
+use super::params::ParamDecl;
+use super::validate::{self, ShaderError};
+use naga::front::glsl::{Frontend, Options};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+/// Names the WGSL prelude defines; anything in the translated shader
+/// called the same gets a suffix
+const RESERVED: &[&str] = &["u", "params", "vs_main", "fs_main"];
+
+/// GLSL declarations matching `Uniforms` in common.wgsl and the
+/// Shadertoy inputs defined on top of them
+const UNIFORMS: &str = "\
+#version 450
+layout(set = 0, binding = 0) uniform HyprfreshUniforms {
+    float time;
+    float opacity;
+    vec2 resolution;
+    uint frame;
+    vec4 date;
+} hyprfresh_u;
+#define iTime hyprfresh_u.time
+#define iResolution vec3(hyprfresh_u.resolution, 1.0)
+#define iFrame int(hyprfresh_u.frame)
+#define iDate hyprfresh_u.date
+#define iMouse vec4(0.0)
+layout(location = 0) in vec2 hyprfresh_uv;
+layout(location = 0) out vec4 hyprfresh_color;
+";
+
+/// Calls `mainImage` with Shadertoy's pixel coordinates (origin at the
+/// bottom left, like our uv)
+const MAIN: &str = "
+void main() {
+    vec4 color = vec4(0.0);
+    mainImage(color, hyprfresh_uv * hyprfresh_u.resolution);
+    hyprfresh_color = vec4(color.rgb, 1.0);
+}
+";
+
+/// GLSL uniform block holding the shader's parameters, laid out like the
+/// WGSL `Params` struct. GLSL blocks can't be empty, so no block without
+/// parameters.
+fn params_block(decls: &[ParamDecl]) -> String {
+    let fields: Vec<String> = decls
+        .iter()
+        .filter_map(|decl| Some(format!("    {} {};\n", decl.ty.glsl()?, decl.name)))
+        .collect();
+    if fields.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("layout(set = 0, binding = 1) uniform HyprfreshParams {\n");
+    out.push_str(&fields.concat());
+    out.push_str("} params;\n");
+    out
+}
+
+/// Translate a Shadertoy-style shader into a WGSL fragment shader with
+/// the same header, ready for `validate`
+pub fn translate(source: &str) -> Result<String, ShaderError> {
+    let header = header::parse(source).map_err(ShaderError::new)?;
+    let prelude = format!("{}{}", UNIFORMS, params_block(&header.params));
+    let prelude_lines = prelude.matches('\n').count();
+
+    // Our prelude supplies the #version; blank the shader's own so line
+    // numbers stay put
+    let body: Vec<&str> = source
+        .lines()
+        .map(|line| if line.trim_start().starts_with("#version") { "" } else { line })
+        .collect();
+    let glsl = format!("{}{}\n{}", prelude, body.join("\n"), MAIN);
+
+    let mut module = Frontend::default()
+        .parse(&Options::from(naga::ShaderStage::Fragment), &glsl)
+        .map_err(|e| {
+            let Some(first) = e.errors.first() else {
+                return ShaderError::new(e.to_string());
+            };
+            let location = first.location(&glsl).and_then(|loc| {
+                let line = (loc.line_number as usize).checked_sub(prelude_lines)?;
+                (line > 0 && line <= body.len()).then_some((line, loc.line_position as usize))
+            });
+            ShaderError {
+                file: None,
+                location,
+                message: first.kind.to_string(),
+            }
+        })?;
+
+    for ep in module.entry_points.iter_mut() {
+        ep.name = "fs_main".to_string();
+    }
+    let rename = |name: &mut Option<String>| {
+        if let Some(n) = name
+            && RESERVED.contains(&n.as_str())
+        {
+            n.push_str("_glsl");
+        }
+    };
+    for (_, var) in module.global_variables.iter_mut() {
+        rename(&mut var.name);
+    }
+    for (_, constant) in module.constants.iter_mut() {
+        rename(&mut constant.name);
+    }
+    for (_, function) in module.functions.iter_mut() {
+        rename(&mut function.name);
+    }
+
+    let info = Validator::new(ValidationFlags::all(), Capabilities::default())
+        .validate(&module)
+        .map_err(|e| ShaderError::new(validate::error_chain(&e)))?;
+    let wgsl = naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty())
+        .map_err(|e| ShaderError::new(format!("failed to translate to WGSL: {}", e)))?;
+
+    // Keep the header so `--list` and the options still work
+    let header_lines: Vec<&str> = source
+        .lines()
+        .take_while(|line| line.trim().is_empty() || line.trim_start().starts_with("//"))
+        .collect();
+    let translated = format!("{}\n{}", header_lines.join("\n"), wgsl);
+
+    // The generated WGSL has no useful line numbers for the author
+    validate::validate(&translated)
+        .map_err(|e| ShaderError::new(format!("translated shader is invalid: {}", e.message)))?;
+    Ok(translated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RINGS: &str = "\
+// @name Rings
+// @param speed: f32 = 1.0 in 0.0..4.0
+// @param tint: vec3<f32> = [1.0, 0.5, 0.2]
+
+float u(float x) { return fract(x); }
+
+void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+    vec2 p = (2.0 * fragCoord - iResolution.xy) / iResolution.y;
+    float r = u(length(p) * 4.0 - iTime * params.speed);
+    fragColor = vec4(params.tint * r + 0.01 * float(iFrame) * iDate.w + iMouse.x, 1.0);
+}
+";
+
+    #[test]
+    fn translates_shadertoy_shader() {
+        let wgsl = translate(RINGS).unwrap();
+        let header = validate::validate(&wgsl).unwrap();
+        assert_eq!(header.name.as_deref(), Some("Rings"));
+        let names: Vec<&str> = header.params.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["speed", "tint"]);
+
+        // No parameters, and a #version line of its own
+        translate("#version 300 es\nvoid mainImage(out vec4 c, in vec2 p) { c = vec4(p / iResolution.xy, 0.0, 1.0); }\n")
+            .unwrap();
+    }
+
+    #[test]
+    fn reports_errors_in_the_glsl_file() {
+        let err = translate("// @name Broken\n\nvoid mainImage(out vec4 c, in vec2 p) {\n    c = vec4(nope);\n}\n")
+            .unwrap_err();
+        assert_eq!(err.location.map(|(line, _)| line), Some(4), "{}", err);
+
+        let err = translate("void notMainImage() {}\n").unwrap_err();
+        assert!(err.location.is_none() && err.message.contains("mainImage"), "{}", err);
+    }
+}
@@ -0,0 +1,170 @@
+//! Shader metadata header
+//!
+//! A `.wgsl` screensaver opens with a comment block describing it:
+//!
+//! ```text
+//! // @name Matrix
+//! // @description Matrix digital rain effect
+//! // @author Jane Doe
+//! // @param speed: f32 = 1.0 in 0.1..5.0 -- Animation speed multiplier
+//! // @image logo -- Logo shown in the middle
+//! // @pass trails
+//! ```
+//!
+//! The header is the run of comment and blank lines at the top of the file;
+//! ordinary comments in it are ignored. Every field is optional, so plain
+//! shaders without a header still work. See `params` for the `@param` syntax,
+//! `images` for `@image` and `passes` for `@pass`.
+
+use super::images::ImageDecl;
+use super::params::ParamDecl;
+use super::passes;
+use serde::Serialize;
+
+/// Metadata parsed from a shader's header
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ShaderHeader {
+    /// Display name (the screensaver is still selected by file name)
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub params: Vec<ParamDecl>,
+    pub images: Vec<ImageDecl>,
+    /// Offscreen passes, in the order they run
+    pub passes: Vec<String>,
+}
+
+impl ShaderHeader {
+    /// Look up a declared parameter
+    pub fn param(&self, name: &str) -> Option<&ParamDecl> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    /// Look up a declared image
+    pub fn image(&self, name: &str) -> Option<&ImageDecl> {
+        self.images.iter().find(|i| i.name == name)
+    }
+}
+
+/// Parse the metadata header of a shader
+pub fn parse(source: &str) -> Result<ShaderHeader, String> {
+    let mut header = ShaderHeader::default();
+
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix("//") else {
+            break;
+        };
+        let Some(directive) = comment.trim().strip_prefix('@') else {
+            continue;
+        };
+        let err = |msg: String| format!("line {}: {}", i + 1, msg);
+
+        let (key, value) = directive
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive, ""));
+        let value = value.trim();
+        let text = |field: &mut Option<String>| {
+            if value.is_empty() {
+                return Err(err(format!("@{} needs a value", key)));
+            }
+            if field.is_some() {
+                return Err(err(format!("duplicate @{}", key)));
+            }
+            *field = Some(value.to_string());
+            Ok(())
+        };
+
+        match key {
+            "name" => text(&mut header.name)?,
+            "description" => text(&mut header.description)?,
+            "author" => text(&mut header.author)?,
+            "param" => {
+                let decl = ParamDecl::parse(value).map_err(err)?;
+                if header.param(&decl.name).is_some() {
+                    return Err(err(format!("duplicate parameter '{}'", decl.name)));
+                }
+                header.params.push(decl);
+            }
+            "image" => {
+                let decl = ImageDecl::parse(value).map_err(err)?;
+                if header.image(&decl.name).is_some() || header.passes.contains(&decl.name) {
+                    return Err(err(format!("duplicate texture '{}'", decl.name)));
+                }
+                header.images.push(decl);
+            }
+            "pass" => {
+                let name = passes::parse(value).map_err(err)?;
+                if header.passes.contains(&name) || header.image(&name).is_some() {
+                    return Err(err(format!("duplicate texture '{}'", name)));
+                }
+                header.passes.push(name);
+            }
+            _ => {
+                return Err(err(format!(
+                    "unknown header field @{} (expected @name, @description, @author, @param, @image or @pass)",
+                    key
+                )));
+            }
+        }
+    }
+
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screensavers::params::ParamValue;
+
+    #[test]
+    fn parses_header_fields() {
+        let header = parse(
+            "\
+// @name Rain
+// @description Falling drops
+// Free-form comments are ignored
+// @author Jane Doe
+//
+// @param speed: f32 = 1.0 in 0.1..5.0 -- Fall speed
+
+// @param tint: vec3<f32> = [0.2, 0.4, 1.0]
+// @image logo = \"logo.png\" -- Logo
+// @pass trail
+fn hash() {}
+// @param not_in_header: f32 = 1.0
+",
+        )
+        .unwrap();
+        assert_eq!(header.name.as_deref(), Some("Rain"));
+        assert_eq!(header.description.as_deref(), Some("Falling drops"));
+        assert_eq!(header.author.as_deref(), Some("Jane Doe"));
+        let names: Vec<&str> = header.params.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["speed", "tint"]);
+        assert_eq!(header.param("tint").unwrap().default, ParamValue::Vec3([0.2, 0.4, 1.0]));
+        assert_eq!(header.image("logo").unwrap().default.as_deref(), Some("logo.png"));
+        assert_eq!(header.passes, ["trail"]);
+
+        assert_eq!(parse("@fragment\nfn fs_main() {}\n").unwrap(), ShaderHeader::default());
+    }
+
+    #[test]
+    fn reports_bad_headers_with_line() {
+        for (source, line) in [
+            ("// @name\n", 1),
+            ("// @name A\n// @name B\n", 2),
+            ("//\n// @autor Jane\n", 2),
+            ("// @param a: f32 = 1.0\n// @param a: f32 = 2.0\n", 2),
+            ("\n// @param speed: f64 = 1.0\n", 2),
+            ("// @pass a\n// @pass a\n", 2),
+            ("// @image a\n// @pass a\n", 2),
+            ("// @pass\n", 1),
+        ] {
+            let err = parse(source).unwrap_err();
+            assert!(err.starts_with(&format!("line {}:", line)), "{}: {}", source, err);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Image inputs
+//!
+//! A shader can sample images whose paths are given in its options. Each
+//! one is declared in the header (see `header`), optionally with a default
+//! path and a description:
+//!
+//! ```text
+//! // @image logo -- Logo shown in the middle of the screen
+//! // @image clouds = "textures/clouds.png" -- Tiling cloud texture
+//! ```
+//!
+//! The option of the same name (a string) picks the file, falling back to
+//! the default; `~` is expanded and relative paths are resolved against the
+//! custom shader directory. PNG, JPEG, GIF and WebP files are supported
+//! (the first frame of an animated one).
+//!
+//! Images are bound in the texture group (`passes::PASS_GROUP`) after the
+//! pass textures: `image_sampler` (linear, repeating), then one
+//! `texture_2d<f32>` per image, named after it. `textureDimensions` gives an
+//! image's size. An image that isn't set or can't be loaded is a single
+//! transparent pixel, so shaders should still draw something sensible.
+
+use super::passes;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// One `// @image` declaration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImageDecl {
+    pub name: String,
+    /// Path used when the option isn't set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ImageDecl {
+    /// Parse the text after `@image`: `name [= "path"] [-- description]`
+    pub fn parse(decl: &str) -> Result<Self, String> {
+        // The name ends at `=` or `--`; a quoted default may contain either
+        let end = decl.find(['=', '-']).unwrap_or(decl.len());
+        let name = decl[..end].trim();
+        let mut rest = &decl[end..];
+        let mut default = None;
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let len = quoted_len(after).unwrap_or_else(|| after.find("--").unwrap_or(after.len()));
+            default = Some(after[..len].trim());
+            rest = &after[len..];
+        }
+        let description = match rest.trim_start() {
+            "" => None,
+            text => match text.strip_prefix("--") {
+                Some(desc) => Some(desc.trim().to_string()),
+                None => return Err(format!("invalid image declaration '{}'", decl.trim())),
+            },
+        };
+
+        if name.is_empty()
+            || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            || name.starts_with(|c: char| c.is_ascii_digit())
+        {
+            return Err(format!("invalid image name '{}'", name));
+        }
+        let default = match default {
+            None => None,
+            Some(text) => match format!("v = {}", text).parse::<toml::Table>() {
+                Ok(table) if table["v"].is_str() => table["v"].as_str().map(str::to_string),
+                _ => return Err(format!("invalid default '{}' (expected a quoted path)", text)),
+            },
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            default,
+            description: description.filter(|d| !d.is_empty()),
+        })
+    }
+
+    /// The file an option names, which must exist
+    pub fn check(&self, value: &toml::Value) -> Result<PathBuf, String> {
+        let path = value
+            .as_str()
+            .map(resolve)
+            .ok_or_else(|| format!("expected a path, got {}", value))?;
+        if !path.is_file() {
+            return Err(format!("{} doesn't exist", path.display()));
+        }
+        Ok(path)
+    }
+
+    /// The file to load given a screensaver's options, if any
+    pub fn path(&self, options: &HashMap<String, toml::Value>) -> Option<PathBuf> {
+        match options.get(&self.name) {
+            Some(value) => value.as_str().map(resolve),
+            None => self.default.as_deref().map(resolve),
+        }
+    }
+}
+
+/// Length of the TOML string that `text` starts with, quotes included
+fn quoted_len(text: &str) -> Option<usize> {
+    let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(i + 1),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Expand `~` and resolve a relative path against the custom shader
+/// directory
+pub fn resolve(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Ok(home) = std::env::var("HOME")
+    {
+        return PathBuf::from(home).join(rest);
+    }
+    let path = PathBuf::from(path);
+    match super::custom_shader_dir() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
+
+/// Decode an image to RGBA, scaled down to fit `max_size` on both sides
+pub fn load(path: &std::path::Path, max_size: u32) -> Result<image::RgbaImage, String> {
+    let image = image::open(path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?;
+    let image = if image.width() > max_size || image.height() > max_size {
+        image.resize(max_size, max_size, image::imageops::FilterType::Triangle)
+    } else {
+        image
+    };
+    Ok(image.into_rgba8())
+}
+
+/// Binding of `image_sampler`, after the textures of `passes` passes
+pub fn sampler_binding(passes: usize) -> u32 {
+    passes::binding(passes)
+}
+
+/// Binding of an image's texture
+pub fn binding(passes: usize, index: usize) -> u32 {
+    sampler_binding(passes) + 1 + index as u32
+}
+
+/// WGSL declarations of the images and their sampler, for a shader with
+/// `passes` passes. Nothing if there are no images.
+pub fn wgsl(passes: usize, images: &[ImageDecl]) -> String {
+    if images.is_empty() {
+        return String::new();
+    }
+    let mut out = format!(
+        "@group({}) @binding({}) var image_sampler: sampler;\n",
+        passes::PASS_GROUP,
+        sampler_binding(passes)
+    );
+    for (i, image) in images.iter().enumerate() {
+        out.push_str(&format!(
+            "@group({}) @binding({}) var {}: texture_2d<f32>;\n",
+            passes::PASS_GROUP,
+            binding(passes, i),
+            image.name
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_declarations() {
+        let logo = ImageDecl::parse("logo -- Logo to show").unwrap();
+        assert_eq!(logo.default, None);
+        assert_eq!(logo.description.as_deref(), Some("Logo to show"));
+        let clouds = ImageDecl::parse("clouds = \"textures/clouds.png\"").unwrap();
+        assert_eq!(clouds.default.as_deref(), Some("textures/clouds.png"));
+        let dashed = ImageDecl::parse("art = \"~/art--2024/a=b.png\" -- Cover art").unwrap();
+        assert_eq!(dashed.default.as_deref(), Some("~/art--2024/a=b.png"));
+        assert_eq!(dashed.description.as_deref(), Some("Cover art"));
+
+        for bad in ["", "my-logo", "logo = textures/clouds.png", "logo = 1", "logo = \"a.png\" b"] {
+            assert!(ImageDecl::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn options_pick_the_file() {
+        let decl = ImageDecl::parse("logo = \"/usr/share/logo.png\"").unwrap();
+        assert_eq!(decl.path(&HashMap::new()), Some(PathBuf::from("/usr/share/logo.png")));
+        let options = HashMap::from([("logo".to_string(), toml::Value::String("/tmp/a.jpg".into()))]);
+        assert_eq!(decl.path(&options), Some(PathBuf::from("/tmp/a.jpg")));
+
+        assert!(decl.check(&toml::Value::Integer(1)).unwrap_err().contains("expected a path"));
+        assert!(decl.check(&toml::Value::String("/nonexistent/a.png".into())).is_err());
+    }
+
+    #[test]
+    fn loads_and_shrinks_images() {
+        let path = std::env::temp_dir().join(format!("hyprfresh_test_image_{}.png", std::process::id()));
+        image::RgbaImage::from_pixel(64, 16, image::Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+        let image = load(&path, 32).unwrap();
+        assert_eq!(image.dimensions(), (32, 8));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(load(&path, 32).is_err());
+    }
+
+    #[test]
+    fn declares_images_after_the_passes() {
+        let images = [ImageDecl::parse("logo").unwrap()];
+        assert_eq!(wgsl(0, &[]), "");
+        let decls = wgsl(1, &images);
+        assert!(decls.contains("@group(1) @binding(2) var image_sampler: sampler;"), "{}", decls);
+        assert!(decls.contains("@group(1) @binding(3) var logo: texture_2d<f32>;"), "{}", decls);
+    }
+}
@@ -0,0 +1,47 @@
+use super::params::ParamBlock;
+use super::Screensaver;
+use std::collections::HashMap;
+
+pub const SHADER: &str = include_str!("../../screensavers/shaders/life.wgsl");
+
+/// Game of Life screensaver - the board is kept between frames in the
+/// shader's `life` pass, one generation per frame
+pub struct Life {
+    params: ParamBlock,
+    time: f32,
+}
+
+impl Life {
+    pub fn new() -> Self {
+        Self {
+            params: super::header_params(SHADER),
+            time: 0.0,
+        }
+    }
+}
+
+impl Screensaver for Life {
+    fn name(&self) -> &str {
+        "life"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        SHADER
+    }
+
+    fn init(&mut self, _width: u32, _height: u32, options: &HashMap<String, toml::Value>) {
+        super::configure_params("life", &mut self.params, options);
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
+    }
+}
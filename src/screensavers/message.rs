@@ -0,0 +1,223 @@
+use super::params::ParamBlock;
+use super::{SaverText, Screensaver};
+use crate::text::Align;
+use log::warn;
+use std::collections::HashMap;
+
+pub const SHADER: &str = include_str!("../../screensavers/shaders/message.wgsl");
+
+/// Direction bouncing text sets off in, steep enough not to run along an edge
+const HEADING: [f32; 2] = [0.8, 0.6];
+
+/// How the text moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Animation {
+    /// Diagonally, bouncing off the edges
+    Bounce,
+    /// Right to left across the middle, coming back round
+    Marquee,
+    /// Not at all, in the middle
+    Still,
+}
+
+impl Animation {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bounce" => Some(Self::Bounce),
+            "marquee" => Some(Self::Marquee),
+            "still" => Some(Self::Still),
+            _ => None,
+        }
+    }
+}
+
+/// Message screensaver - lines of text over a plain background. The
+/// renderer lays the text out and draws it (see `Screensaver::text`); this
+/// only moves it around.
+pub struct Message {
+    params: ParamBlock,
+    time: f32,
+    animation: Animation,
+    /// Pixels per second
+    speed: f32,
+    screen: [f32; 2],
+    /// Size the text was last laid out at
+    text_size: [f32; 2],
+    /// Top left corner of the text, once it's been laid out
+    position: Option<[f32; 2]>,
+    /// Which way bouncing text is heading
+    heading: [f32; 2],
+}
+
+impl Message {
+    pub fn new() -> Self {
+        Self {
+            params: super::header_params(SHADER),
+            time: 0.0,
+            animation: Animation::Bounce,
+            speed: 80.0,
+            screen: [0.0; 2],
+            text_size: [0.0; 2],
+            position: None,
+            heading: HEADING,
+        }
+    }
+
+    /// Where text of the current size sits on the screen when centred
+    fn centre(&self) -> [f32; 2] {
+        [0, 1].map(|axis| (self.screen[axis] - self.text_size[axis]) / 2.0)
+    }
+
+    /// Room the text has to move in, per axis
+    fn free(&self) -> [f32; 2] {
+        [0, 1].map(|axis| (self.screen[axis] - self.text_size[axis]).max(0.0))
+    }
+}
+
+impl Screensaver for Message {
+    fn name(&self) -> &str {
+        "message"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        SHADER
+    }
+
+    fn init(&mut self, width: u32, height: u32, options: &HashMap<String, toml::Value>) {
+        super::configure_params("message", &mut self.params, options);
+        self.speed = self.params.number("speed", 80.0);
+        self.screen = [width as f32, height as f32];
+
+        let name = self.params.string("animation", "bounce");
+        let animation = Animation::parse(name).unwrap_or_else(|| {
+            warn!("Screensaver 'message': unknown animation '{}', using bounce", name);
+            Animation::Bounce
+        });
+        // A new message carries on from where the old one was
+        if animation != self.animation {
+            self.animation = animation;
+            self.position = None;
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.time += dt;
+        let Some(mut position) = self.position else {
+            return;
+        };
+        let step = self.speed * dt;
+        match self.animation {
+            Animation::Bounce => {
+                let free = self.free();
+                for axis in 0..2 {
+                    let p = &mut position[axis];
+                    *p += self.heading[axis] * step;
+                    if *p < 0.0 {
+                        *p = -*p;
+                        self.heading[axis] = self.heading[axis].abs();
+                    }
+                    if *p > free[axis] {
+                        *p = 2.0 * free[axis] - *p;
+                        self.heading[axis] = -self.heading[axis].abs();
+                    }
+                    *p = p.clamp(0.0, free[axis]);
+                }
+            }
+            Animation::Marquee => {
+                // Once off the left edge, back in from the right
+                let span = self.screen[0] + self.text_size[0];
+                let travelled = self.screen[0] - position[0] + step;
+                position[0] = self.screen[0] - travelled.rem_euclid(span.max(1.0));
+            }
+            Animation::Still => {}
+        }
+        self.position = Some(position);
+    }
+
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
+    }
+
+    fn text(&self) -> Option<SaverText<'_>> {
+        let font = self.params.string("font", "");
+        let [r, g, b] = self.params.vec3("color", [1.0; 3]);
+        Some(SaverText {
+            text: self.params.string("message", ""),
+            font: (!font.is_empty()).then_some(font),
+            size: self.params.number("size", 96.0),
+            color: [r, g, b, 1.0],
+            align: Align::Center,
+        })
+    }
+
+    fn place_text(&mut self, size: [f32; 2]) -> [f32; 2] {
+        self.text_size = size;
+        let centre = self.centre();
+        let free = self.free();
+        let position = match (self.animation, self.position) {
+            (Animation::Still, _) | (Animation::Bounce, None) => centre,
+            // Still on screen if the text or the screen changed size
+            (Animation::Bounce, Some([x, y])) => [x.clamp(0.0, free[0]), y.clamp(0.0, free[1])],
+            (Animation::Marquee, None) => [self.screen[0], centre[1]],
+            (Animation::Marquee, Some([x, _])) => [x, centre[1]],
+        };
+        self.position = Some(position);
+        // Whole pixels keep the glyphs sharp
+        position.map(f32::round)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(animation: &str) -> HashMap<String, toml::Value> {
+        HashMap::from([
+            ("animation".to_string(), toml::Value::String(animation.into())),
+            ("speed".to_string(), toml::Value::Float(500.0)),
+        ])
+    }
+
+    #[test]
+    fn bounces_inside_the_screen_and_scrolls_round() {
+        let mut saver = Message::new();
+        saver.init(1920, 1080, &options("bounce"));
+        assert_eq!(saver.text().unwrap().text, "Away from keyboard");
+
+        // Centred until it moves
+        let size = [600.0, 200.0];
+        assert_eq!(saver.place_text(size), [660.0, 440.0]);
+        let mut corners = Vec::new();
+        for _ in 0..600 {
+            saver.update(0.05);
+            let [x, y] = saver.place_text(size);
+            assert!((0.0..=1320.0).contains(&x) && (0.0..=880.0).contains(&y));
+            corners.push(saver.heading);
+        }
+        // It came back off both edges on both axes
+        corners.dedup();
+        assert!(corners.len() > 4);
+
+        // A new message keeps the text where it was
+        let before = saver.place_text(size);
+        let mut changed = options("bounce");
+        changed.insert("message".to_string(), toml::Value::String("Back at 14:30\nping me on chat".into()));
+        saver.init(1920, 1080, &changed);
+        assert_eq!(saver.place_text(size), before);
+        assert_eq!(saver.text().unwrap().text, "Back at 14:30\nping me on chat");
+
+        // Marquee: in from the right edge, across, and round again
+        saver.init(1920, 1080, &options("marquee"));
+        assert_eq!(saver.place_text(size), [1920.0, 440.0]);
+        saver.update(1.0);
+        assert_eq!(saver.place_text(size), [1420.0, 440.0]);
+        saver.update(4.0);
+        assert_eq!(saver.place_text(size), [-580.0, 440.0]);
+        saver.update(0.1);
+        assert_eq!(saver.place_text(size), [1890.0, 440.0]);
+    }
+}
@@ -1,30 +1,145 @@
 //! Screensaver modules
 //!
 //! Each screensaver implements the `Screensaver` trait, providing
-//! initialization, per-frame updates, and a fragment shader or
-//! draw commands for rendering.
+//! initialization, per-frame updates, and a fragment shader for rendering.
+//! `BUILTIN` is the one list of built-in screensavers; the renderer,
+//! `--list` and config checking all go through it.
 //!
 //! Custom shaders can be placed in `~/.config/hypr/hyprfresh/shaders/`.
 //! Any `.wgsl` file in that directory becomes available as a screensaver
-//! using the filename (without extension) as the name.
-
+//! using the filename (without extension) as the name; so does any
+//! Shadertoy-style `.glsl` file (see `glsl`). Shaders describe themselves
+//! and their options in a comment header (see `header`), and WGSL shaders
+//! can import the bundled library or shared files (see `preprocess`) and
+//! render offscreen passes that persist between frames (see `passes`) and
+//! sample images named in their options (see `images`).
+
+pub mod animation;
 pub mod blank;
+pub mod custom;
+pub mod fireflies;
+pub mod glsl;
+pub mod header;
+pub mod images;
+pub mod life;
 pub mod matrix;
+pub mod message;
+pub mod params;
+pub mod passes;
 pub mod plasmula;
+pub mod preprocess;
+pub mod rng;
+pub mod slideshow;
 pub mod starfield;
-
-use log::debug;
+pub mod validate;
+
+use crate::text::Align;
+use custom::CustomShader;
+use header::ShaderHeader;
+use images::ImageDecl;
+use log::{debug, warn};
+use params::{ParamBlock, ParamDecl};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-
-/// All built-in screensaver names
-pub const BUILTIN: &[(&str, &str)] = &[
-    ("blank", "Black screen (DPMS-like, minimal power)"),
-    ("matrix", "Matrix digital rain effect"),
-    ("plasmula", "Dracula-themed plasma waves"),
-    ("starfield", "Classic starfield fly-through"),
+use std::sync::Arc;
+
+/// All built-in screensavers
+const BUILTIN: &[fn() -> Box<dyn Screensaver>] = &[
+    || Box::new(animation::Animation::new()),
+    || Box::new(blank::Blank::new()),
+    || Box::new(fireflies::Fireflies::new()),
+    || Box::new(life::Life::new()),
+    || Box::new(matrix::Matrix::new()),
+    || Box::new(message::Message::new()),
+    || Box::new(plasmula::Plasmula::new()),
+    || Box::new(slideshow::Slideshow::new()),
+    || Box::new(starfield::Starfield::new()),
 ];
 
+/// Common vertex shader and uniforms (shared by all screensavers)
+pub const COMMON_SHADER: &str = include_str!("../../screensavers/shaders/common.wgsl");
+
+/// Everything the renderer puts in front of a fragment shader: the common
+/// vertex stage and uniforms, then the shader's parameter block, pass
+/// textures and images
+pub fn prelude(header: &ShaderHeader) -> String {
+    format!(
+        "{}\n{}\n{}{}",
+        COMMON_SHADER,
+        params::wgsl(&header.params),
+        passes::wgsl(&header.passes),
+        images::wgsl(header.passes.len(), &header.images)
+    )
+}
+
+/// A fresh instance of every built-in screensaver
+pub fn builtins() -> impl Iterator<Item = Box<dyn Screensaver>> {
+    BUILTIN.iter().map(|create| create())
+}
+
+/// A fresh instance of a built-in screensaver
+pub fn builtin(name: &str) -> Option<Box<dyn Screensaver>> {
+    builtins().find(|saver| saver.name() == name)
+}
+
+/// Fragment shader source of a built-in screensaver
+pub fn builtin_source(name: &str) -> Option<String> {
+    builtin(name).map(|saver| saver.fragment_shader().to_string())
+}
+
+/// Instantiate a screensaver by name: a custom shader if there is one
+/// (it overrides a built-in of the same name), else the built-in
+pub fn create(name: &str) -> Result<Box<dyn Screensaver>, String> {
+    if let Some(path) = custom_shader_path(name) {
+        return Ok(Box::new(CustomShader::load(name, &path)?));
+    }
+    builtin(name).ok_or_else(|| format!("Unknown screensaver '{}'", name))
+}
+
+/// Parameter block with the defaults declared in a shader's header
+fn header_params(source: &str) -> ParamBlock {
+    ParamBlock::new(header::parse(source).map(|h| h.params).unwrap_or_default())
+}
+
+/// Fill a screensaver's parameter block from its options, logging any
+/// that can't be used
+fn configure_params(name: &str, params: &mut ParamBlock, options: &HashMap<String, toml::Value>) {
+    for warning in params.configure(options) {
+        warn!("Screensaver '{}': {}", name, warning);
+    }
+}
+
+/// Clock behind `u.time` for savers with a `speed` option: seconds since
+/// the saver started, scaled by `speed`
+#[derive(Debug, Clone, Copy)]
+pub struct AnimationClock {
+    time: f32,
+    speed: f32,
+}
+
+impl AnimationClock {
+    pub fn new() -> Self {
+        Self { time: 0.0, speed: 1.0 }
+    }
+
+    /// Take the speed from the saver's `speed` parameter
+    pub fn configure(&mut self, params: &ParamBlock) {
+        self.speed = params.number("speed", 1.0);
+    }
+
+    /// Advance by `dt` real seconds; returns the animation seconds that passed
+    pub fn tick(&mut self, dt: f32) -> f32 {
+        let dt = dt * self.speed;
+        self.time += dt;
+        dt
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
+
 /// Default directory for custom shaders
 const CUSTOM_SHADER_DIR: &str = "~/.config/hypr/hyprfresh/shaders";
 
@@ -39,6 +154,19 @@ pub fn custom_shader_dir() -> Option<PathBuf> {
     }
 }
 
+/// Extensions of custom shader files, in order of preference when a
+/// directory has both
+const CUSTOM_SHADER_EXTENSIONS: &[&str] = &["wgsl", "glsl"];
+
+/// Path of a custom shader, if there is one
+pub fn custom_shader_path(name: &str) -> Option<PathBuf> {
+    let dir = custom_shader_dir()?;
+    CUSTOM_SHADER_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+}
+
 /// Discover custom shaders from the filesystem
 pub fn discover_custom() -> Vec<(String, PathBuf)> {
     let Some(dir) = custom_shader_dir() else {
@@ -52,7 +180,9 @@ pub fn discover_custom() -> Vec<(String, PathBuf)> {
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if path.extension().is_some_and(|ext| ext == "wgsl")
+        if path
+            .extension()
+            .is_some_and(|ext| CUSTOM_SHADER_EXTENSIONS.iter().any(|e| ext == *e))
             && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
         {
             let name = stem.to_string();
@@ -61,89 +191,303 @@ pub fn discover_custom() -> Vec<(String, PathBuf)> {
         }
     }
 
-    custom.sort_by(|a, b| a.0.cmp(&b.0));
+    // `rain.wgsl` wins over `rain.glsl`
+    let rank = |path: &Path| {
+        CUSTOM_SHADER_EXTENSIONS
+            .iter()
+            .position(|ext| path.extension().is_some_and(|e| e == *ext))
+    };
+    custom.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| rank(&a.1).cmp(&rank(&b.1))));
+    custom.dedup_by(|a, b| a.0 == b.0);
     custom
 }
 
-/// Load a custom shader's fragment source from disk
+/// Load a custom shader's WGSL fragment source from disk, translating
+/// `.glsl` files and rejecting anything that wouldn't compile (see
+/// `validate`)
 pub fn load_custom_shader(path: &Path) -> Result<String, String> {
     let source = std::fs::read_to_string(path)
         .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file = path.display().to_string();
+    if path.extension().is_some_and(|ext| ext == "glsl") {
+        return glsl::translate(&source).map_err(|e| e.render(&file));
+    }
+    validate::validate(&source).map_err(|e| e.render(&file))?;
+    Ok(source)
+}
 
-    // Basic validation: must contain fs_main entry point
-    if !source.contains("fn fs_main") {
-        return Err(format!(
-            "Shader {} missing required 'fn fs_main' entry point",
-            path.display()
-        ));
+/// Files a screensaver's shader is read from, for watching: the custom
+/// shader paths, whether or not they exist yet (creating one overrides a
+/// built-in), and the files it currently imports. Empty if there's no
+/// custom shader directory.
+pub fn shader_files(name: &str) -> Vec<PathBuf> {
+    let Some(dir) = custom_shader_dir() else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = CUSTOM_SHADER_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .collect();
+    let source = custom_shader_path(name)
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .or_else(|| builtin_source(name));
+    if let Some(source) = source
+        && let Ok(expanded) = preprocess::expand(&source, Some(&dir))
+    {
+        files.extend(expanded.files);
     }
+    files
+}
 
-    Ok(source)
+/// Expand a fragment shader's imports (see `preprocess`). `name` is used
+/// in errors about the shader itself.
+pub fn expand_imports(name: &str, fragment: &str) -> Result<String, String> {
+    preprocess::expand(fragment, custom_shader_dir().as_deref())
+        .map(|expanded| expanded.source)
+        .map_err(|e| e.render(name))
+}
+
+/// Metadata header of a screensaver (custom shaders win over built-ins,
+/// as in the renderer). None if the name is unknown or the shader is broken.
+pub fn header(name: &str) -> Option<ShaderHeader> {
+    let source = match custom_shader_path(name) {
+        Some(path) => load_custom_shader(&path).ok(),
+        None => builtin_source(name),
+    }?;
+    header::parse(&source).ok()
 }
 
 /// Check if a screensaver name is valid (built-in or valid custom shader)
 pub fn is_valid(name: &str) -> bool {
-    if BUILTIN.iter().any(|(n, _)| *n == name) {
-        return true;
-    }
-    // Check for custom shader file AND validate it has fs_main
-    if let Some(dir) = custom_shader_dir() {
-        let path = dir.join(format!("{}.wgsl", name));
-        if path.is_file() {
-            return load_custom_shader(&path).is_ok();
-        }
+    resolve(name).is_ok()
+}
+
+/// Like `is_valid`, but explains why a name can't be used
+pub fn resolve(name: &str) -> Result<(), String> {
+    if builtin(name).is_some() {
+        return Ok(());
+    }
+    // A custom .wgsl or .glsl shader, loaded and fully validated
+    if let Some(path) = custom_shader_path(name) {
+        return load_custom_shader(&path).map(|_| ());
     }
-    false
+    Err(format!(
+        "unknown screensaver '{}' (available: {})",
+        name,
+        available_names().join(", ")
+    ))
+}
+
+/// Names of all built-in and custom screensavers, sorted
+pub fn available_names() -> Vec<String> {
+    let mut names: Vec<String> = builtins().map(|saver| saver.name().to_string()).collect();
+    names.extend(discover_custom().into_iter().map(|(n, _)| n));
+    names.sort();
+    names.dedup();
+    names
 }
 
-/// Trait that all screensaver modules must implement
-#[allow(dead_code)]
+/// Trait that all screensaver modules must implement.
+///
+/// The renderer creates one instance per monitor, calls `init` whenever the
+/// surface gets a new size, and `update` before every frame; `time` and
+/// `params` are then uploaded for the shader.
 pub trait Screensaver {
-    /// Human-readable name
+    /// Name used in the config
     fn name(&self) -> &str;
 
-    /// Short description
-    fn description(&self) -> &str;
+    /// WGSL fragment shader source, starting with its metadata header
+    /// (which also describes the screensaver and its options)
+    fn fragment_shader(&self) -> &str;
 
-    /// Initialize the screensaver with the given viewport dimensions
+    /// Initialize the screensaver with the given viewport dimensions.
+    /// Called again on resize, so it shouldn't reset the animation.
     fn init(&mut self, width: u32, height: u32, options: &HashMap<String, toml::Value>);
 
     /// Update state for the next frame (dt = seconds since last frame)
     fn update(&mut self, dt: f32);
 
-    /// Return the WGSL fragment shader source for this screensaver
-    /// The shader receives: time (f32), resolution (vec2f), and any uniforms
-    fn fragment_shader(&self) -> &str;
+    /// Animation clock passed to the shader as `u.time`. Built-in savers
+    /// with a `speed` option scale it (see `AnimationClock`), so their
+    /// shaders use it as is; custom shaders get plain seconds.
+    fn time(&self) -> f32;
+
+    /// Contents of the shader's `params` uniform block
+    fn params(&self) -> &[u8];
+
+    /// Per-frame data computed in Rust (particles, grids, ...), one slice
+    /// per storage buffer. They are uploaded after every `update` and bound
+    /// in order from `@group(0) @binding(2)`, which the shader declares as
+    /// `var<storage, read>`. The number of buffers is fixed, their sizes may
+    /// change; a runtime-sized array needs at least one element.
+    fn storage(&self) -> Vec<&[u8]> {
+        Vec::new()
+    }
+
+    /// Images the screensaver decoded itself since the last call, as
+    /// (index among its `@image`s, pixels). Polled after every `update`; the
+    /// renderer replaces those textures, which otherwise come from the
+    /// options (see `images`). Shared, so a saver can hand over an image it
+    /// keeps without copying it.
+    fn take_images(&mut self) -> Vec<(usize, Arc<image::RgbaImage>)> {
+        Vec::new()
+    }
+
+    /// Text to draw over the shader with the renderer's text pipeline (see
+    /// `text`), if any. Asked for after every `update`.
+    fn text(&self) -> Option<SaverText<'_>> {
+        None
+    }
+
+    /// Where the text goes: given the size it was laid out at in pixels,
+    /// the top left corner to draw it at
+    fn place_text(&mut self, _size: [f32; 2]) -> [f32; 2] {
+        [0.0; 2]
+    }
+}
+
+/// Text a screensaver shows (see `Screensaver::text`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaverText<'a> {
+    pub text: &'a str,
+    /// Font file, or None for the built-in font
+    pub font: Option<&'a str>,
+    /// Line height in pixels
+    pub size: f32,
+    /// RGB and opacity
+    pub color: [f32; 4],
+    pub align: Align,
+}
+
+/// A screensaver as shown by `--list`
+#[derive(Debug, Serialize)]
+pub struct ShaderInfo {
+    pub name: String,
+    /// `@name` from the header
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub builtin: bool,
+    /// File a custom shader was loaded from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    pub overrides_builtin: bool,
+    pub params: Vec<ParamDecl>,
+    pub images: Vec<ImageDecl>,
+    /// Why a custom shader can't be used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ShaderInfo {
+    fn new(name: &str, header: Result<ShaderHeader, String>) -> Self {
+        let (header, error) = match header {
+            Ok(h) => (h, None),
+            Err(e) => (ShaderHeader::default(), Some(e)),
+        };
+        Self {
+            name: name.to_string(),
+            display_name: header.name,
+            description: header.description,
+            author: header.author,
+            builtin: true,
+            path: None,
+            overrides_builtin: false,
+            params: header.params,
+            images: header.images,
+            error,
+        }
+    }
 }
 
-/// List all available screensavers (built-in + custom)
-pub fn list_available() {
+/// Built-in screensavers followed by custom shaders, with their headers
+pub fn describe_all() -> (Vec<ShaderInfo>, Vec<ShaderInfo>) {
+    let builtin_infos = builtins()
+        .map(|saver| ShaderInfo::new(saver.name(), header::parse(saver.fragment_shader())))
+        .collect();
+    let custom = discover_custom()
+        .into_iter()
+        .map(|(name, path)| {
+            let mut info = ShaderInfo::new(&name, load_custom_shader(&path).and_then(|s| header::parse(&s)));
+            info.builtin = false;
+            info.overrides_builtin = builtin(&name).is_some();
+            info.path = Some(path);
+            info
+        })
+        .collect();
+    (builtin_infos, custom)
+}
+
+/// `--list --json` output
+#[derive(Debug, Serialize)]
+struct Listing<'a> {
+    screensavers: Vec<ShaderInfo>,
+    presets: Vec<PresetInfo<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct PresetInfo<'a> {
+    name: &'a str,
+    description: &'a str,
+}
+
+/// List all available screensavers (built-in + custom) and the config's
+/// presets, given as (name, description) pairs
+pub fn list_available(presets: &[(String, String)], json: bool) {
+    let (builtin, custom) = describe_all();
+
+    if json {
+        let listing = Listing {
+            screensavers: builtin.into_iter().chain(custom).collect(),
+            presets: presets
+                .iter()
+                .map(|(name, description)| PresetInfo { name, description })
+                .collect(),
+        };
+        match serde_json::to_string_pretty(&listing) {
+            Ok(out) => println!("{}", out),
+            Err(e) => eprintln!("Failed to encode listing: {}", e),
+        }
+        return;
+    }
+
     println!("Available screensavers:");
     println!();
 
     // Built-in
-    for (name, desc) in BUILTIN {
-        println!("  {:<16} {}", name, desc);
+    for info in &builtin {
+        print_info(info, "");
     }
 
     // Custom
-    let custom = discover_custom();
     if !custom.is_empty() {
         println!();
         println!("Custom shaders ({}):", CUSTOM_SHADER_DIR);
         println!();
-        for (name, path) in &custom {
-            let overrides = if BUILTIN.iter().any(|(n, _)| *n == name) {
-                " (overrides built-in)"
-            } else {
-                ""
-            };
-            println!(
-                "  {:<16} {}{}",
-                name,
-                path.file_name().unwrap_or_default().to_string_lossy(),
-                overrides
-            );
+        for info in &custom {
+            let mut notes = vec![info
+                .path
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()];
+            if let Some(ref author) = info.author {
+                notes.push(format!("by {}", author));
+            }
+            if info.overrides_builtin {
+                notes.push("overrides built-in".to_string());
+            }
+            print_info(info, &format!(" ({})", notes.join(", ")));
+        }
+    }
+
+    if !presets.is_empty() {
+        println!();
+        println!("Presets:");
+        println!();
+        for (name, desc) in presets {
+            println!("  {:<16} {}", name, desc);
         }
     }
 
@@ -152,18 +496,33 @@ pub fn list_available() {
     println!("  [screensaver]");
     println!("  name = \"matrix\"");
     println!();
-    println!("Custom shaders: place .wgsl files in {}", CUSTOM_SHADER_DIR);
+    println!("Custom shaders: place .wgsl or Shadertoy .glsl files in {}", CUSTOM_SHADER_DIR);
 }
 
-/// Get a screensaver instance by name
-#[allow(dead_code)]
-pub fn get(name: &str) -> Option<Box<dyn Screensaver>> {
-    match name {
-        "blank" => Some(Box::new(blank::Blank::new())),
-        "matrix" => Some(Box::new(matrix::Matrix::new())),
-        "plasmula" => Some(Box::new(plasmula::Plasmula::new())),
-        "starfield" => Some(Box::new(starfield::Starfield::new())),
-        _ => None,
+/// One `--list` entry: description, then the options it accepts
+fn print_info(info: &ShaderInfo, suffix: &str) {
+    // The error already names the file
+    if let Some(ref error) = info.error {
+        println!("  {:<16} invalid, using blank: {}", info.name, error);
+        return;
+    }
+    let description = info
+        .description
+        .as_deref()
+        .or(info.display_name.as_deref())
+        .unwrap_or("");
+    println!("  {:<16} {}{}", info.name, description, suffix);
+    let options: Vec<String> = info
+        .params
+        .iter()
+        .map(|p| format!("{} = {}", p.name, p.default))
+        .chain(info.images.iter().map(|i| match i.default {
+            Some(ref path) => format!("{} = {:?}", i.name, path),
+            None => format!("{} (image)", i.name),
+        }))
+        .collect();
+    if !options.is_empty() {
+        println!("  {:<16} options: {}", "", options.join(", "));
     }
 }
 
@@ -173,18 +532,60 @@ mod tests {
 
     #[test]
     fn builtin_names_are_valid() {
+        assert!(is_valid("animation"));
         assert!(is_valid("blank"));
+        assert!(is_valid("fireflies"));
+        assert!(is_valid("life"));
 
         assert!(is_valid("matrix"));
+        assert!(is_valid("message"));
         assert!(is_valid("plasmula"));
+        assert!(is_valid("slideshow"));
 
 
         assert!(is_valid("starfield"));
     }
 
+    #[test]
+    fn builtin_headers_describe_savers() {
+        for saver in builtins() {
+            let header = header::parse(saver.fragment_shader()).unwrap();
+            assert!(header.description.is_some(), "{}", saver.name());
+        }
+        let matrix = header("matrix").unwrap();
+        assert_eq!(matrix.name.as_deref(), Some("Matrix"));
+        assert!(matrix.param("density").is_some());
+    }
+
+    #[test]
+    fn savers_parse_options_and_drive_their_clock() {
+        let mut matrix = builtin("matrix").unwrap();
+        let options = HashMap::from([
+            ("speed".to_string(), toml::Value::Float(2.0)),
+            ("density".to_string(), toml::Value::Float(3.0)),
+        ]);
+        matrix.init(1920, 1080, &options);
+        matrix.update(0.5);
+        matrix.update(0.25);
+        assert_eq!(matrix.time(), 1.5);
+        // speed @0, density @4
+        assert_eq!(&matrix.params()[4..8], &3.0f32.to_ne_bytes());
+
+        // Resizing doesn't restart the animation
+        matrix.init(1280, 720, &options);
+        assert_eq!(matrix.time(), 1.5);
+
+        let mut blank = builtin("blank").unwrap();
+        blank.update(0.5);
+        assert_eq!(blank.time(), 0.5);
+        assert!(builtin("nonexistent").is_none());
+    }
+
     #[test]
     fn unknown_name_is_invalid() {
         assert!(!is_valid("nonexistent_shader_xyz"));
+        let err = resolve("nonexistent_shader_xyz").unwrap_err();
+        assert!(err.contains("available:") && err.contains("matrix"), "{}", err);
     }
 
     #[test]
@@ -0,0 +1,470 @@
+//! Shader parameters
+//!
+//! A shader declares its tunable parameters in its header (see `header`):
+//!
+//! ```text
+//! // @param speed: f32 = 1.0 in 0.1..5.0 -- Animation speed
+//! // @param stars: i32 = 200 in 1..1000
+//! // @param twinkle: bool = true
+//! // @param color: vec3<f32> = [0.0, 1.0, 0.0] -- RGB tint
+//! // @param folder: string = "~/Pictures" -- Where to look
+//! ```
+//!
+//! The range (`in MIN..MAX`, numbers only) and the description are optional.
+//!
+//! The renderer turns the declarations into a `Params` uniform struct bound
+//! at `@group(0) @binding(1)` as `params`, and fills it from the merged
+//! `[screensaver.options]`, falling back to the declared defaults. `bool`
+//! parameters become `u32` fields (0 or 1), since WGSL uniforms can't hold
+//! booleans. `string` parameters are only for the screensaver's Rust side
+//! and aren't part of the block.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Type of a shader parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ParamType {
+    #[serde(rename = "f32")]
+    Float,
+    #[serde(rename = "i32")]
+    Int,
+    #[serde(rename = "bool")]
+    Bool,
+    #[serde(rename = "vec3<f32>")]
+    Vec3,
+    #[serde(rename = "string")]
+    Str,
+}
+
+impl ParamType {
+    fn parse(ty: &str) -> Option<Self> {
+        match ty {
+            "f32" => Some(Self::Float),
+            "i32" => Some(Self::Int),
+            "bool" => Some(Self::Bool),
+            "vec3<f32>" | "vec3f" => Some(Self::Vec3),
+            "string" => Some(Self::Str),
+            _ => None,
+        }
+    }
+
+    /// Type of the generated WGSL field, None if not in the uniform block
+    fn wgsl(self) -> Option<&'static str> {
+        match self {
+            Self::Float => Some("f32"),
+            Self::Int => Some("i32"),
+            Self::Bool => Some("u32"),
+            Self::Vec3 => Some("vec3<f32>"),
+            Self::Str => None,
+        }
+    }
+
+    /// Type of the field in a GLSL uniform block, None if not in the block
+    pub fn glsl(self) -> Option<&'static str> {
+        match self {
+            Self::Float => Some("float"),
+            Self::Int => Some("int"),
+            Self::Bool => Some("uint"),
+            Self::Vec3 => Some("vec3"),
+            Self::Str => None,
+        }
+    }
+
+    /// (alignment, size) in a WGSL uniform buffer, None if not in it
+    fn layout(self) -> Option<(usize, usize)> {
+        match self {
+            Self::Vec3 => Some((16, 12)),
+            Self::Str => None,
+            _ => Some((4, 4)),
+        }
+    }
+
+    /// Name used in messages and docs
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Float => "f32",
+            Self::Int => "i32",
+            Self::Bool => "bool",
+            Self::Vec3 => "vec3<f32>",
+            Self::Str => "string",
+        }
+    }
+}
+
+/// A concrete parameter value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Float(f32),
+    Int(i32),
+    Bool(bool),
+    Vec3([f32; 3]),
+    Str(String),
+}
+
+impl ParamValue {
+    /// Convert a TOML option to a value of type `ty`.
+    /// Integers are accepted where floats are expected.
+    pub fn from_toml(ty: ParamType, value: &toml::Value) -> Result<Self, String> {
+        let number = |v: &toml::Value| match v {
+            toml::Value::Float(f) => Some(*f as f32),
+            toml::Value::Integer(i) => Some(*i as f32),
+            _ => None,
+        };
+        let parsed = match (ty, value) {
+            (ParamType::Float, v) => number(v).map(Self::Float),
+            (ParamType::Int, toml::Value::Integer(i)) => i32::try_from(*i).ok().map(Self::Int),
+            (ParamType::Bool, toml::Value::Boolean(b)) => Some(Self::Bool(*b)),
+            (ParamType::Vec3, toml::Value::Array(items)) if items.len() == 3 => {
+                match (number(&items[0]), number(&items[1]), number(&items[2])) {
+                    (Some(x), Some(y), Some(z)) => Some(Self::Vec3([x, y, z])),
+                    _ => None,
+                }
+            }
+            (ParamType::Str, toml::Value::String(s)) => Some(Self::Str(s.clone())),
+            _ => None,
+        };
+        parsed.ok_or_else(|| format!("expected {}, got {}", ty.name(), value))
+    }
+
+    fn as_number(&self) -> Option<f32> {
+        match *self {
+            Self::Float(f) => Some(f),
+            Self::Int(i) => Some(i as f32),
+            _ => None,
+        }
+    }
+
+    fn write(&self, out: &mut [u8]) {
+        match *self {
+            Self::Float(f) => out[..4].copy_from_slice(&f.to_ne_bytes()),
+            Self::Int(i) => out[..4].copy_from_slice(&i.to_ne_bytes()),
+            Self::Bool(b) => out[..4].copy_from_slice(&u32::from(b).to_ne_bytes()),
+            Self::Vec3(v) => out[..12].copy_from_slice(bytemuck::cast_slice(&v)),
+            Self::Str(_) => {}
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Float(v) => write!(f, "{:?}", v),
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Bool(v) => write!(f, "{}", v),
+            Self::Vec3(v) => write!(f, "{:?}", v),
+            Self::Str(v) => write!(f, "{:?}", v),
+        }
+    }
+}
+
+/// One `// @param` declaration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamDecl {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: ParamType,
+    pub default: ParamValue,
+    /// Inclusive (min, max) for numeric parameters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<(f32, f32)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ParamDecl {
+    /// Parse the text after `@param`: `name: type = default [in MIN..MAX] [-- description]`
+    pub fn parse(decl: &str) -> Result<Self, String> {
+        let (decl, description) = match decl.split_once("--") {
+            Some((decl, desc)) => (decl, Some(desc.trim().to_string())),
+            None => (decl, None),
+        };
+        let (name, rest) = decl
+            .split_once(':')
+            .ok_or("expected `@param name: type = default`")?;
+        let (ty, rest) = rest.split_once('=').ok_or("missing `= default`")?;
+        let (default, range) = match rest.split_once(" in ") {
+            Some((default, range)) => (default, Some(range.trim())),
+            None => (rest, None),
+        };
+
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid parameter name '{}'", name));
+        }
+        let ty = ParamType::parse(ty.trim()).ok_or_else(|| {
+            format!("unsupported type '{}' (use f32, i32, bool, vec3<f32> or string)", ty.trim())
+        })?;
+        let default = parse_value(ty, default.trim())
+            .map_err(|e| format!("invalid default '{}': {}", default.trim(), e))?;
+
+        let range = match range {
+            None => None,
+            Some(_) if !matches!(ty, ParamType::Float | ParamType::Int) => {
+                return Err(format!("{} parameters can't have a range", ty.name()));
+            }
+            Some(range) => {
+                let bounds = range.split_once("..").and_then(|(min, max)| {
+                    Some((parse_value(ty, min.trim()).ok()?, parse_value(ty, max.trim()).ok()?))
+                });
+                match bounds.map(|(min, max)| (min.as_number(), max.as_number())) {
+                    Some((Some(min), Some(max))) if min <= max => Some((min, max)),
+                    _ => return Err(format!("invalid range '{}' (expected MIN..MAX)", range)),
+                }
+            }
+        };
+
+        let decl = Self {
+            name: name.to_string(),
+            ty,
+            default,
+            range,
+            description: description.filter(|d| !d.is_empty()),
+        };
+        decl.check_range(&decl.default).map_err(|e| format!("default {}", e))?;
+        Ok(decl)
+    }
+
+    /// Convert an option value, enforcing the declared type and range
+    pub fn check(&self, value: &toml::Value) -> Result<ParamValue, String> {
+        let value = ParamValue::from_toml(self.ty, value)?;
+        self.check_range(&value)?;
+        Ok(value)
+    }
+
+    fn check_range(&self, value: &ParamValue) -> Result<(), String> {
+        if let (Some((min, max)), Some(v)) = (self.range, value.as_number())
+            && !(min..=max).contains(&v)
+        {
+            return Err(format!("{} is outside {}..{}", value, min, max));
+        }
+        Ok(())
+    }
+}
+
+fn parse_value(ty: ParamType, text: &str) -> Result<ParamValue, String> {
+    let table = format!("v = {}", text)
+        .parse::<toml::Table>()
+        .map_err(|_| "not a value".to_string())?;
+    ParamValue::from_toml(ty, &table["v"])
+}
+
+/// WGSL declarations for the parameter block
+pub fn wgsl(decls: &[ParamDecl]) -> String {
+    let mut out = String::from("struct Params {\n");
+    let mut fields = 0;
+    for decl in decls {
+        if let Some(ty) = decl.ty.wgsl() {
+            out.push_str(&format!("    {}: {},\n", decl.name, ty));
+            fields += 1;
+        }
+    }
+    if fields == 0 {
+        // WGSL structs can't be empty
+        out.push_str("    _unused: u32,\n");
+    }
+    out.push_str("}\n\n@group(0) @binding(1) var<uniform> params: Params;\n");
+    out
+}
+
+/// A screensaver's parameter block: the declared parameters, their
+/// current values and the bytes uploaded for the shader
+#[derive(Debug, Clone)]
+pub struct ParamBlock {
+    decls: Vec<ParamDecl>,
+    values: Vec<ParamValue>,
+    /// Byte offset of each parameter in `data`, None for strings
+    offsets: Vec<Option<usize>>,
+    data: Vec<u8>,
+}
+
+impl ParamBlock {
+    /// A block holding every parameter's default
+    pub fn new(decls: Vec<ParamDecl>) -> Self {
+        let mut offsets = Vec::with_capacity(decls.len());
+        let mut end = 0usize;
+        for decl in &decls {
+            let offset = decl.ty.layout().map(|(align, size)| {
+                let offset = end.next_multiple_of(align);
+                end = offset + size;
+                offset
+            });
+            offsets.push(offset);
+        }
+
+        // Uniform buffers are sized in 16-byte units; an empty block still
+        // holds the placeholder field
+        let mut block = Self {
+            values: decls.iter().map(|d| d.default.clone()).collect(),
+            decls,
+            offsets,
+            data: vec![0u8; end.max(4).next_multiple_of(16)],
+        };
+        for i in 0..block.values.len() {
+            block.write(i);
+        }
+        block
+    }
+
+    /// Copy a parameter's value into `data`
+    fn write(&mut self, i: usize) {
+        if let Some(offset) = self.offsets[i] {
+            self.values[i].write(&mut self.data[offset..]);
+        }
+    }
+
+    /// Set every parameter from `options`, using defaults for unset or
+    /// mistyped values. Returns one message per option that couldn't be used.
+    pub fn configure(&mut self, options: &HashMap<String, toml::Value>) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for i in 0..self.decls.len() {
+            let decl = &self.decls[i];
+            let value = match options.get(&decl.name) {
+                Some(v) => decl.check(v).unwrap_or_else(|e| {
+                    warnings.push(format!("option '{}': {}, using default", decl.name, e));
+                    decl.default.clone()
+                }),
+                None => decl.default.clone(),
+            };
+            self.values[i] = value;
+            self.write(i);
+        }
+        warnings
+    }
+
+    /// Current value of a parameter
+    pub fn get(&self, name: &str) -> Option<&ParamValue> {
+        let i = self.decls.iter().position(|d| d.name == name)?;
+        Some(&self.values[i])
+    }
+
+    /// Current value of a numeric parameter as f32, or `fallback`
+    pub fn number(&self, name: &str, fallback: f32) -> f32 {
+        self.get(name).and_then(ParamValue::as_number).unwrap_or(fallback)
+    }
+
+    /// Current value of a bool parameter, or `fallback`
+    pub fn flag(&self, name: &str, fallback: bool) -> bool {
+        match self.get(name) {
+            Some(ParamValue::Bool(b)) => *b,
+            _ => fallback,
+        }
+    }
+
+    /// Current value of a vec3 parameter, or `fallback`
+    pub fn vec3(&self, name: &str, fallback: [f32; 3]) -> [f32; 3] {
+        match self.get(name) {
+            Some(ParamValue::Vec3(v)) => *v,
+            _ => fallback,
+        }
+    }
+
+    /// Current value of a string parameter, or `fallback`
+    pub fn string<'a>(&'a self, name: &str, fallback: &'a str) -> &'a str {
+        match self.get(name) {
+            Some(ParamValue::Str(s)) => s,
+            _ => fallback,
+        }
+    }
+
+    /// Contents of the uniform buffer
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decls() -> Vec<ParamDecl> {
+        [
+            "speed: f32 = 1.5 in 0.1..5.0 -- Fall speed",
+            "stars: i32 = 200",
+            "twinkle: bool = true",
+            "color: vec3<f32> = [0.0, 1.0, 0.0]",
+            "folder: string = \"~/Pictures\"",
+        ]
+        .iter()
+        .map(|d| ParamDecl::parse(d).unwrap())
+        .collect()
+    }
+
+    fn f32_at(data: &[u8], offset: usize) -> f32 {
+        f32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn parses_declarations() {
+        let decls = decls();
+        assert_eq!(decls[0].default, ParamValue::Float(1.5));
+        assert_eq!(decls[0].range, Some((0.1, 5.0)));
+        assert_eq!(decls[0].description.as_deref(), Some("Fall speed"));
+        assert_eq!(decls[1].default, ParamValue::Int(200));
+        assert_eq!(decls[2].default, ParamValue::Bool(true));
+        assert_eq!(decls[3].default, ParamValue::Vec3([0.0, 1.0, 0.0]));
+        assert_eq!(decls[4].default, ParamValue::Str("~/Pictures".into()));
+        assert!(wgsl(&decls).contains("    twinkle: u32,\n"));
+        assert!(!wgsl(&decls).contains("folder"));
+        assert!(wgsl(&[]).contains("_unused"));
+    }
+
+    #[test]
+    fn rejects_bad_declarations() {
+        for bad in [
+            "speed f32 = 1.0",
+            "speed: f32",
+            "speed: f64 = 1.0",
+            "speed: f32 = fast",
+            "stars: i32 = 1.5",
+            "speed: f32 = 1.0 in 5..1",
+            "speed: f32 = 9.0 in 0..5",
+            "twinkle: bool = true in 0..1",
+            "folder: string = ~/Pictures",
+        ] {
+            assert!(ParamDecl::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn checks_option_types_and_ranges() {
+        let speed = &decls()[0];
+        assert_eq!(speed.check(&toml::Value::Integer(2)), Ok(ParamValue::Float(2.0)));
+        assert!(speed.check(&toml::Value::Float(7.5)).unwrap_err().contains("outside"));
+        assert!(speed.check(&toml::Value::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn fills_block_from_options_over_defaults() {
+        let decls = decls();
+        let options = HashMap::from([
+            ("speed".to_string(), toml::Value::Integer(3)),
+            ("twinkle".to_string(), toml::Value::String("yes".into())),
+            (
+                "color".to_string(),
+                toml::Value::Array(vec![1.0.into(), 0.5.into(), 0.0.into()]),
+            ),
+        ]);
+        let mut block = ParamBlock::new(decls);
+        let warnings = block.configure(&options);
+        let data = block.bytes();
+
+        // speed @0, stars @4, twinkle @8, color @16 (vec3 is 16-aligned)
+        assert_eq!(data.len(), 32);
+        assert_eq!(f32_at(data, 0), 3.0);
+        assert_eq!(i32::from_ne_bytes(data[4..8].try_into().unwrap()), 200);
+        assert_eq!(u32::from_ne_bytes(data[8..12].try_into().unwrap()), 1);
+        assert_eq!(f32_at(data, 16), 1.0);
+        assert_eq!(f32_at(data, 20), 0.5);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("twinkle"));
+
+        assert_eq!(block.get("speed"), Some(&ParamValue::Float(3.0)));
+        assert_eq!(block.number("stars", 0.0), 200.0);
+        assert!(block.flag("twinkle", false));
+        assert_eq!(block.string("folder", ""), "~/Pictures");
+        assert_eq!(ParamBlock::new(Vec::new()).bytes().len(), 16);
+    }
+}
@@ -0,0 +1,92 @@
+//! Offscreen render passes
+//!
+//! A shader can declare passes in its header (see `header`), in the order
+//! they run:
+//!
+//! ```text
+//! // @pass life
+//! ```
+//!
+//! Each pass is a fragment entry point named `fs_<pass>` that renders into
+//! its own texture, sized to the output and kept between frames, before
+//! `fs_main` draws to the screen. The textures are bound at `@group(1)` as
+//! `texture_2d<f32>` variables named after their passes, with
+//! `pass_sampler` at binding 0. A pass sees the textures of earlier passes
+//! as rendered this frame, its own and later ones as they were at the end
+//! of the last frame. `fs_main` sees everything from this frame.
+//!
+//! Textures start out zeroed, and again whenever the output is resized, so a
+//! pass that keeps state should treat alpha 0 as "not initialised yet".
+
+/// Bind group holding the pass textures (and images, see `images`)
+pub const PASS_GROUP: u32 = 1;
+
+/// Binding of a pass's texture in `PASS_GROUP`, after `pass_sampler`
+pub fn binding(index: usize) -> u32 {
+    index as u32 + 1
+}
+
+/// Fragment entry point of a pass
+pub fn entry_point(name: &str) -> String {
+    format!("fs_{}", name)
+}
+
+/// Check a pass name from `@pass`
+pub fn parse(name: &str) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("@pass needs a name".to_string());
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || name.starts_with(|c: char| c.is_ascii_digit())
+    {
+        return Err(format!("invalid pass name '{}'", name));
+    }
+    if name == "main" {
+        return Err("'main' is the final pass (fs_main), not an offscreen one".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// WGSL declarations of the pass textures, their sampler and `sample_pass`,
+/// which takes a uv as `fs_main` gets it (origin at the bottom left).
+/// Nothing if there are no passes.
+pub fn wgsl(passes: &[String]) -> String {
+    if passes.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("@group({}) @binding(0) var pass_sampler: sampler;\n", PASS_GROUP);
+    for (i, name) in passes.iter().enumerate() {
+        out.push_str(&format!(
+            "@group({}) @binding({}) var {}: texture_2d<f32>;\n",
+            PASS_GROUP,
+            binding(i),
+            name
+        ));
+    }
+    out.push_str(
+        "\nfn sample_pass(tex: texture_2d<f32>, uv: vec2<f32>) -> vec4<f32> {\n    \
+         return textureSample(tex, pass_sampler, vec2<f32>(uv.x, 1.0 - uv.y));\n}\n",
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pass_names_must_be_identifiers() {
+        assert_eq!(parse("trail_2").unwrap(), "trail_2");
+        for name in ["", "2d", "my-pass", "main"] {
+            assert!(parse(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn declares_one_texture_per_pass() {
+        assert_eq!(wgsl(&[]), "");
+        let decls = wgsl(&["a".to_string(), "b".to_string()]);
+        assert!(decls.contains("@group(1) @binding(0) var pass_sampler: sampler;"), "{}", decls);
+        assert!(decls.contains("@group(1) @binding(2) var b: texture_2d<f32>;"), "{}", decls);
+    }
+}
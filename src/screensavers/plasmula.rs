@@ -1,13 +1,21 @@
-use super::Screensaver;
+use super::params::ParamBlock;
+use super::{AnimationClock, Screensaver};
 use std::collections::HashMap;
 
+pub const SHADER: &str = include_str!("../../screensavers/shaders/plasmula.wgsl");
+
 /// Plasmula screensaver - Dracula-themed plasma waves
-#[allow(dead_code)]
-pub struct Plasmula;
+pub struct Plasmula {
+    params: ParamBlock,
+    clock: AnimationClock,
+}
 
 impl Plasmula {
     pub fn new() -> Self {
-        Self
+        Self {
+            params: super::header_params(SHADER),
+            clock: AnimationClock::new(),
+        }
     }
 }
 
@@ -16,15 +24,24 @@ impl Screensaver for Plasmula {
         "plasmula"
     }
 
-    fn description(&self) -> &str {
-        "Dracula-themed plasma waves"
+    fn fragment_shader(&self) -> &str {
+        SHADER
+    }
+
+    fn init(&mut self, _width: u32, _height: u32, options: &HashMap<String, toml::Value>) {
+        super::configure_params("plasmula", &mut self.params, options);
+        self.clock.configure(&self.params);
     }
 
-    fn init(&mut self, _width: u32, _height: u32, _options: &HashMap<String, toml::Value>) {}
+    fn update(&mut self, dt: f32) {
+        self.clock.tick(dt);
+    }
 
-    fn update(&mut self, _dt: f32) {}
+    fn time(&self) -> f32 {
+        self.clock.time()
+    }
 
-    fn fragment_shader(&self) -> &str {
-        include_str!("../../screensavers/shaders/plasmula.wgsl")
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
     }
 }
@@ -0,0 +1,247 @@
+//! `#import` / `#include` for WGSL shaders
+//!
+//! A line `#import NAME` (or `#include "NAME"`, they are the same) is
+//! replaced by the contents of NAME:
+//!
+//! - `hyprfresh/MODULE` is a module of the bundled library: `hash`, `noise`,
+//!   `palette`, `sdf` and `color`
+//! - anything else is a file relative to the custom shader directory, with
+//!   `.wgsl` added if it has no extension. Keep such files in a
+//!   subdirectory, or they are listed as screensavers themselves.
+//!
+//! Each file is included once per shader, however often it is imported, so
+//! library modules can import each other freely; importing a file from
+//! itself (directly or not) is an error. The expanded source keeps a map
+//! back to the original files for error messages.
+
+use super::validate::ShaderError;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The bundled library: (module, source)
+const LIBRARY: &[(&str, &str)] = &[
+    ("color", include_str!("../../screensavers/shaders/lib/color.wgsl")),
+    ("hash", include_str!("../../screensavers/shaders/lib/hash.wgsl")),
+    ("noise", include_str!("../../screensavers/shaders/lib/noise.wgsl")),
+    ("palette", include_str!("../../screensavers/shaders/lib/palette.wgsl")),
+    ("sdf", include_str!("../../screensavers/shaders/lib/sdf.wgsl")),
+];
+
+/// Prefix of library modules
+const LIBRARY_PREFIX: &str = "hyprfresh/";
+
+/// A shader with its imports expanded
+#[derive(Debug, Clone)]
+pub struct Expanded {
+    pub source: String,
+    /// On-disk files that were included
+    pub files: Vec<PathBuf>,
+    /// Names of the included files as shown in errors; the shader itself
+    /// is index 0
+    names: Vec<String>,
+    /// (index into `names`, 1-based line) of every line of `source`
+    lines: Vec<(usize, usize)>,
+}
+
+impl Expanded {
+    /// Where a 1-based line of the expanded source came from: the included
+    /// file (None for the shader itself) and its line there
+    pub fn origin(&self, line: usize) -> Option<(Option<&str>, usize)> {
+        let &(file, line) = self.lines.get(line.checked_sub(1)?)?;
+        Some(((file > 0).then(|| self.names[file].as_str()), line))
+    }
+}
+
+/// Expand the imports of `source`, resolving files in `dir` (the custom
+/// shader directory)
+pub fn expand(source: &str, dir: Option<&Path>) -> Result<Expanded, ShaderError> {
+    let mut expander = Expander {
+        dir,
+        out: Expanded {
+            source: String::new(),
+            files: Vec::new(),
+            names: vec![String::new()],
+            lines: Vec::new(),
+        },
+        stack: Vec::new(),
+        done: HashSet::new(),
+    };
+    expander.expand_file(0, source)?;
+    Ok(expander.out)
+}
+
+/// The target of an import directive, if `line` is one
+fn directive(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let rest = line
+        .strip_prefix("#import")
+        .or_else(|| line.strip_prefix("#include"))?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let target = rest.trim();
+    let unquoted = target
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .or_else(|| target.strip_prefix('<').and_then(|t| t.strip_suffix('>')));
+    Some(unquoted.unwrap_or(target))
+}
+
+/// An import resolved to its contents
+struct Import {
+    /// Identifies the file for cycle and duplicate detection
+    key: String,
+    /// Shown in errors
+    name: String,
+    source: String,
+    path: Option<PathBuf>,
+}
+
+struct Expander<'a> {
+    dir: Option<&'a Path>,
+    out: Expanded,
+    /// Keys of the files being expanded, outermost first
+    stack: Vec<String>,
+    /// Keys of the files already included
+    done: HashSet<String>,
+}
+
+impl Expander<'_> {
+    fn expand_file(&mut self, file: usize, source: &str) -> Result<(), ShaderError> {
+        for (i, line) in source.lines().enumerate() {
+            let Some(target) = directive(line) else {
+                self.push_line(line, file, i + 1);
+                continue;
+            };
+            let err = |message: String| ShaderError {
+                file: (file > 0).then(|| self.out.names[file].clone()),
+                location: Some((i + 1, 1)),
+                message,
+            };
+            if target.is_empty() {
+                return Err(err("import needs a module or file name".to_string()));
+            }
+
+            let import = self.resolve(target).map_err(err)?;
+            if self.stack.contains(&import.key) {
+                let mut chain: Vec<&str> = self.stack.iter().map(String::as_str).collect();
+                chain.push(&import.key);
+                return Err(err(format!("import cycle: {}", chain.join(" -> "))));
+            }
+            if self.done.contains(&import.key) {
+                // Already included; keep the line numbering intact
+                self.push_line("", file, i + 1);
+                continue;
+            }
+
+            let index = self.out.names.len();
+            self.out.names.push(import.name);
+            self.out.files.extend(import.path);
+            self.stack.push(import.key.clone());
+            self.expand_file(index, &import.source)?;
+            self.stack.pop();
+            self.done.insert(import.key);
+        }
+        Ok(())
+    }
+
+    fn push_line(&mut self, line: &str, file: usize, number: usize) {
+        self.out.source.push_str(line);
+        self.out.source.push('\n');
+        self.out.lines.push((file, number));
+    }
+
+    fn resolve(&self, target: &str) -> Result<Import, String> {
+        if let Some(module) = target.strip_prefix(LIBRARY_PREFIX) {
+            let module = module.strip_suffix(".wgsl").unwrap_or(module);
+            let Some((_, source)) = LIBRARY.iter().find(|(name, _)| *name == module) else {
+                let available: Vec<String> = LIBRARY
+                    .iter()
+                    .map(|(name, _)| format!("{}{}", LIBRARY_PREFIX, name))
+                    .collect();
+                return Err(format!(
+                    "unknown library module '{}' (available: {})",
+                    target,
+                    available.join(", ")
+                ));
+            };
+            let name = format!("{}{}", LIBRARY_PREFIX, module);
+            return Ok(Import {
+                key: name.clone(),
+                name,
+                source: source.to_string(),
+                path: None,
+            });
+        }
+
+        let Some(dir) = self.dir else {
+            return Err(format!("cannot import '{}': no custom shader directory", target));
+        };
+        let mut path = dir.join(target);
+        if path.extension().is_none() {
+            path.set_extension("wgsl");
+        }
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| format!("cannot import '{}': {}: {}", target, path.display(), e))?;
+        let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+        Ok(Import {
+            key: key.display().to_string(),
+            name: path.display().to_string(),
+            source,
+            path: Some(path),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_library_modules_once() {
+        let source = "// @name Test\n#import hyprfresh/noise\n#include \"hyprfresh/hash\"\nfn f() {}\n";
+        let expanded = expand(source, None).unwrap();
+        assert_eq!(expanded.source.matches("fn hash21").count(), 1);
+        assert!(expanded.source.contains("fn value_noise"));
+        assert!(!expanded.source.contains("#import"));
+
+        // Lines map back to the file they came from
+        let last = expanded.source.lines().count();
+        assert_eq!(expanded.origin(last), Some((None, 4)));
+        assert_eq!(expanded.origin(1), Some((None, 1)));
+        let noise_line = expanded
+            .source
+            .lines()
+            .position(|l| l.starts_with("fn value_noise"))
+            .unwrap();
+        let (file, _) = expanded.origin(noise_line + 1).unwrap();
+        assert_eq!(file, Some("hyprfresh/noise"));
+
+        let err = expand("\n#import hyprfresh/nope\n", None).unwrap_err();
+        assert_eq!(err.location, Some((2, 1)));
+        assert!(err.message.contains("hyprfresh/hash"), "{}", err);
+    }
+
+    #[test]
+    fn resolves_files_and_detects_cycles() {
+        let dir = std::env::temp_dir().join(format!("hyprfresh_test_imports_{}", std::process::id()));
+        let lib = dir.join("lib");
+        std::fs::create_dir_all(&lib).unwrap();
+        std::fs::write(lib.join("util.wgsl"), "#import hyprfresh/hash\nfn util() -> f32 { return 1.0; }\n").unwrap();
+        std::fs::write(lib.join("a.wgsl"), "#include \"lib/b.wgsl\"\n").unwrap();
+        std::fs::write(lib.join("b.wgsl"), "\n#import lib/a\n").unwrap();
+
+        let expanded = expand("#import lib/util\n#import hyprfresh/hash\n", Some(&dir)).unwrap();
+        assert!(expanded.source.contains("fn util()") && expanded.source.contains("fn hash21"));
+        assert_eq!(expanded.files, vec![lib.join("util.wgsl")]);
+
+        let err = expand("#import lib/a\n", Some(&dir)).unwrap_err();
+        assert!(err.message.starts_with("import cycle:"), "{}", err);
+        assert_eq!(err.file, Some(lib.join("b.wgsl").display().to_string()));
+        assert_eq!(err.location, Some((2, 1)));
+
+        assert!(expand("#import lib/missing\n", Some(&dir)).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
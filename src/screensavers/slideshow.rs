@@ -0,0 +1,522 @@
+use super::params::ParamBlock;
+use super::rng::Rng;
+use super::{images, Screensaver};
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+
+pub const SHADER: &str = include_str!("../../screensavers/shaders/slideshow.wgsl");
+
+/// How far pan and zoom magnify an image (ZOOM in the shader), so images
+/// are decoded that much larger
+const PAN_ZOOM_MARGIN: f32 = 1.15;
+
+/// Largest side of a decoded image
+const MAX_SIZE: u32 = 8192;
+
+/// How deep to look into subdirectories
+const MAX_DEPTH: usize = 16;
+
+/// Extensions of the files shown
+const EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// Slideshow screensaver - images are found and decoded on a loader thread
+/// and handed to the renderer through `take_images`, alternating between
+/// the shader's two image slots
+pub struct Slideshow {
+    params: ParamBlock,
+    time: f32,
+    /// What the loader was started for; a change restarts it
+    loader_config: Option<LoaderConfig>,
+    /// How slides asked for from now on are sized
+    fit: Fit,
+    requests: Option<mpsc::Sender<Request>>,
+    results: Option<mpsc::Receiver<Loaded>>,
+    /// Images to show, in order; slide `n` is `files[n % files.len()]`
+    files: Vec<PathBuf>,
+    /// Slide in front, if any has been shown
+    shown: Option<usize>,
+    /// Slide the loader is decoding
+    pending: Option<usize>,
+    /// Decoded slide waiting for its turn
+    ready: Option<(usize, image::RgbaImage)>,
+    /// Slide to start from when not in lockstep
+    start: usize,
+    /// Slot (`slide_a` or `slide_b`) of the slide in front
+    front: usize,
+    slots: [Slot; 2],
+    /// Images for the renderer, by slot
+    taken: Vec<(usize, Arc<image::RgbaImage>)>,
+    /// (seconds shown, seed, weight, loaded) per slot, as the shader reads it
+    gpu: [[f32; 4]; 2],
+    rng: Rng,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Slot {
+    age: f32,
+    seed: f32,
+    loaded: bool,
+}
+
+/// What the loader thread lists and in which order
+#[derive(Clone, PartialEq)]
+struct LoaderConfig {
+    directory: PathBuf,
+    /// Seed of the shuffled order, None to sort by path
+    shuffle: Option<u64>,
+}
+
+/// Output size and how images are fitted to it. Sent with every request, so
+/// a resize doesn't restart the loader.
+#[derive(Clone, Copy, Default, PartialEq)]
+struct Fit {
+    size: (u32, u32),
+    fill: bool,
+    pan_zoom: bool,
+}
+
+/// Decode a file for a slide
+struct Request {
+    slide: usize,
+    path: PathBuf,
+    fit: Fit,
+}
+
+/// What the loader thread sends back
+enum Loaded {
+    Files(Vec<PathBuf>),
+    Image {
+        slide: usize,
+        path: PathBuf,
+        /// What it was sized for
+        fit: Fit,
+        result: Result<image::RgbaImage, String>,
+    },
+}
+
+impl Slideshow {
+    pub fn new() -> Self {
+        Self {
+            params: super::header_params(SHADER),
+            time: 0.0,
+            loader_config: None,
+            fit: Fit::default(),
+            requests: None,
+            results: None,
+            files: Vec::new(),
+            shown: None,
+            pending: None,
+            ready: None,
+            start: 0,
+            front: 1,
+            slots: [Slot::default(); 2],
+            taken: Vec::new(),
+            gpu: [[0.0; 4]; 2],
+            rng: Rng::from_time(),
+        }
+    }
+
+    fn lockstep(&self) -> bool {
+        self.params.flag("lockstep", false)
+    }
+
+    fn interval(&self) -> f32 {
+        self.params.number("interval", 10.0).max(1.0)
+    }
+
+    /// Slide every monitor in lockstep shows now
+    fn lockstep_now(&self) -> usize {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        lockstep_slide(now, self.interval())
+    }
+
+    /// Slide to load next
+    fn wanted(&self) -> usize {
+        match (self.shown, self.lockstep()) {
+            (None, true) => self.lockstep_now(),
+            (None, false) => self.start,
+            (Some(shown), true) => (shown + 1).max(self.lockstep_now()),
+            (Some(shown), false) => shown + 1,
+        }
+    }
+
+    /// Whether the slide in front has had its turn
+    fn due(&self, next: usize) -> bool {
+        match self.shown {
+            None => true,
+            Some(_) if self.lockstep() => self.lockstep_now() >= next,
+            Some(_) => self.slots[self.front].age >= self.interval(),
+        }
+    }
+
+    /// (Re)start the loader thread, dropping the old one's work
+    fn start_loader(&mut self, config: LoaderConfig) {
+        let (request_tx, request_rx) = mpsc::channel();
+        let (result_tx, result_rx) = mpsc::channel();
+        let thread_config = config.clone();
+        let spawned = std::thread::Builder::new()
+            .name("slideshow".to_string())
+            .spawn(move || run_loader(thread_config, request_rx, result_tx));
+        if let Err(e) = spawned {
+            warn!("Screensaver 'slideshow': failed to start the loader: {}", e);
+        }
+        self.requests = Some(request_tx);
+        self.results = Some(result_rx);
+        self.loader_config = Some(config);
+        self.files.clear();
+        self.shown = None;
+        self.pending = None;
+        self.ready = None;
+    }
+
+    /// Take in whatever the loader has finished
+    fn poll_loader(&mut self) {
+        let Some(results) = self.results.as_ref() else {
+            return;
+        };
+        let loaded: Vec<Loaded> = results.try_iter().collect();
+        for loaded in loaded {
+            match loaded {
+                Loaded::Files(files) => {
+                    if files.is_empty()
+                        && let Some(ref config) = self.loader_config
+                    {
+                        warn!(
+                            "Screensaver 'slideshow': no images in {}",
+                            config.directory.display()
+                        );
+                    }
+                    self.start = match files.len() {
+                        0 => 0,
+                        n => self.rng.below(n),
+                    };
+                    self.files = files;
+                }
+                Loaded::Image { slide, path, fit, result } => {
+                    self.pending = None;
+                    // Sized for an output we no longer have; asked for again
+                    if fit != self.fit {
+                        continue;
+                    }
+                    match result {
+                        Ok(image) => self.ready = Some((slide, image)),
+                        Err(e) => {
+                            // Don't come back to it
+                            warn!("Screensaver 'slideshow': {}", e);
+                            self.files.retain(|file| *file != path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Ask for the next slide if nothing is on its way
+    fn request_next(&mut self) {
+        if self.files.is_empty() || self.pending.is_some() || self.ready.is_some() {
+            return;
+        }
+        let slide = self.wanted();
+        let path = self.files[slide % self.files.len()].clone();
+        let fit = self.fit;
+        if let Some(ref requests) = self.requests
+            && requests.send(Request { slide, path, fit }).is_ok()
+        {
+            self.pending = Some(slide);
+        }
+    }
+
+    /// Bring the decoded slide to the front once the current one is done
+    fn advance(&mut self) {
+        let Some(next) = self.ready.as_ref().map(|(slide, _)| *slide) else {
+            return;
+        };
+        if !self.due(next) {
+            return;
+        }
+        if let Some((slide, image)) = self.ready.take() {
+            self.front = 1 - self.front;
+            self.slots[self.front] = Slot {
+                age: 0.0,
+                seed: slide_seed(slide),
+                loaded: true,
+            };
+            self.taken.push((self.front, Arc::new(image)));
+            self.shown = Some(slide);
+        }
+    }
+}
+
+impl Screensaver for Slideshow {
+    fn name(&self) -> &str {
+        "slideshow"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        SHADER
+    }
+
+    fn init(&mut self, width: u32, height: u32, options: &HashMap<String, toml::Value>) {
+        super::configure_params("slideshow", &mut self.params, options);
+
+        let directory = self.params.string("directory", "~/Pictures");
+        let directory = images::resolve(directory);
+        // In lockstep every monitor has to come up with the same order;
+        // otherwise keep the one we have, so a resize doesn't reshuffle
+        let old_seed = self.loader_config.as_ref().and_then(|old| old.shuffle);
+        let shuffle = match (self.params.flag("shuffle", false), self.lockstep()) {
+            (false, _) => None,
+            (true, true) => Some(path_seed(&directory)),
+            (true, false) => Some(old_seed.unwrap_or_else(|| self.rng.next_u64())),
+        };
+        let fit = Fit {
+            size: (width, height),
+            fill: self.params.flag("fill", false),
+            pan_zoom: self.params.flag("pan_zoom", true),
+        };
+        if fit != self.fit {
+            // The slide waiting was sized for the old output
+            self.fit = fit;
+            self.ready = None;
+        }
+        let config = LoaderConfig { directory, shuffle };
+        if self.loader_config.as_ref() != Some(&config) {
+            self.start_loader(config);
+        }
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.time += dt;
+        for slot in &mut self.slots {
+            slot.age += dt;
+        }
+
+        self.poll_loader();
+        self.advance();
+        self.request_next();
+
+        let fade = self.params.number("fade", 1.5);
+        let front = self.slots[self.front].age;
+        let weight = if fade > 0.0 { (front / fade).min(1.0) } else { 1.0 };
+        for (i, slot) in self.slots.iter().enumerate() {
+            let weight = if i == self.front { weight } else { 1.0 - weight };
+            self.gpu[i] = [slot.age, slot.seed, weight, f32::from(u8::from(slot.loaded))];
+        }
+    }
+
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn params(&self) -> &[u8] {
+        self.params.bytes()
+    }
+
+    fn storage(&self) -> Vec<&[u8]> {
+        vec![bytemuck::cast_slice(&self.gpu)]
+    }
+
+    fn take_images(&mut self) -> Vec<(usize, Arc<image::RgbaImage>)> {
+        std::mem::take(&mut self.taken)
+    }
+}
+
+/// Loader thread: list the directory, then decode whatever is asked for
+/// until the slideshow goes away
+fn run_loader(config: LoaderConfig, requests: mpsc::Receiver<Request>, results: mpsc::Sender<Loaded>) {
+    let mut files = Vec::new();
+    scan(&config.directory, 0, &mut HashSet::new(), &mut files);
+    files.sort();
+    if let Some(seed) = config.shuffle {
+        shuffle(&mut files, seed);
+    }
+    debug!("Slideshow: {} images in {}", files.len(), config.directory.display());
+    if results.send(Loaded::Files(files)).is_err() {
+        return;
+    }
+
+    for Request { slide, path, fit } in requests {
+        let zoom = if fit.pan_zoom { PAN_ZOOM_MARGIN } else { 1.0 };
+        let result = images::load(&path, MAX_SIZE).map(|image| {
+            let (width, height) = decoded_size(image.dimensions(), fit.size, fit.fill, zoom);
+            if (width, height) == image.dimensions() {
+                image
+            } else {
+                image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle)
+            }
+        });
+        if results.send(Loaded::Image { slide, path, fit, result }).is_err() {
+            return;
+        }
+    }
+}
+
+/// Collect the images under `dir`. Linked directories are followed, but
+/// each directory is only read once (by device and inode), so links back up
+/// the tree don't list the same images again.
+fn scan(dir: &Path, depth: usize, visited: &mut HashSet<(u64, u64)>, files: &mut Vec<PathBuf>) {
+    let Ok(metadata) = std::fs::metadata(dir) else {
+        return;
+    };
+    if !visited.insert((metadata.dev(), metadata.ino())) {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                scan(&path, depth + 1, visited, files);
+            }
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        {
+            files.push(path);
+        }
+    }
+}
+
+/// Fisher-Yates shuffle, the same for the same seed
+fn shuffle(files: &mut [PathBuf], seed: u64) {
+    let mut rng = Rng::new(seed);
+    for i in (1..files.len()).rev() {
+        files.swap(i, rng.below(i + 1));
+    }
+}
+
+/// Seed that's the same for a directory on every monitor (FNV-1a)
+fn path_seed(path: &Path) -> u64 {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// Slide shown at `secs` since the epoch, so monitors agree without talking
+fn lockstep_slide(secs: f64, interval: f32) -> usize {
+    (secs / f64::from(interval)) as usize
+}
+
+/// Seed for a slide's pan direction, in [0, 1)
+fn slide_seed(slide: usize) -> f32 {
+    ((slide as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Size to decode an image at: as large as it's shown on the output
+/// (filling it or fitting inside, with room to zoom), but never upscaled
+fn decoded_size(image: (u32, u32), output: (u32, u32), fill: bool, zoom: f32) -> (u32, u32) {
+    let fit_x = output.0 as f32 / image.0.max(1) as f32;
+    let fit_y = output.1 as f32 / image.1.max(1) as f32;
+    let scale = if fill { fit_x.max(fit_y) } else { fit_x.min(fit_y) };
+    let scale = (scale * zoom).min(1.0);
+    (
+        ((image.0 as f32 * scale).round() as u32).max(1),
+        ((image.1 as f32 * scale).round() as u32).max(1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_are_decoded_at_their_shown_size() {
+        // 4000x2000 on 1920x1080: fitted by width, filled by height
+        assert_eq!(decoded_size((4000, 2000), (1920, 1080), false, 1.0), (1920, 960));
+        assert_eq!(decoded_size((4000, 2000), (1920, 1080), true, 1.0), (2160, 1080));
+        assert_eq!(decoded_size((4000, 2000), (1920, 1080), false, 1.5), (2880, 1440));
+        // Small ones stay as they are
+        assert_eq!(decoded_size((640, 480), (1920, 1080), true, 1.15), (640, 480));
+    }
+
+    #[test]
+    fn order_is_sorted_or_shuffled_the_same_everywhere() {
+        let files: Vec<PathBuf> = (0..20).map(|i| PathBuf::from(format!("{:02}.png", i))).collect();
+        let mut a = files.clone();
+        let mut b = files.clone();
+        shuffle(&mut a, path_seed(Path::new("/pictures")));
+        shuffle(&mut b, path_seed(Path::new("/pictures")));
+        assert_eq!(a, b);
+        assert_ne!(a, files);
+        a.sort();
+        assert_eq!(a, files);
+
+        assert_ne!(path_seed(Path::new("/pictures")), path_seed(Path::new("/photos")));
+        assert_eq!(lockstep_slide(1000.0, 10.0), 100);
+        assert_eq!(lockstep_slide(1009.9, 10.0), 100);
+    }
+
+    #[test]
+    fn shows_images_from_the_directory() {
+        let dir = std::env::temp_dir().join(format!("hyprfresh_slideshow_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        for (i, name) in ["a.png", "sub/b.PNG", "c.png"].iter().enumerate() {
+            image::RgbaImage::from_pixel(40, 20, image::Rgba([i as u8, 0, 0, 255]))
+                .save_with_format(dir.join(name), image::ImageFormat::Png)
+                .unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), "not an image").unwrap();
+        // Links back up the tree are not walked again
+        std::os::unix::fs::symlink(&dir, dir.join("sub/up")).unwrap();
+        std::os::unix::fs::symlink("..", dir.join("sub/parent")).unwrap();
+
+        let mut saver = Slideshow::new();
+        let options = HashMap::from([
+            ("directory".to_string(), toml::Value::String(dir.display().to_string())),
+            ("interval".to_string(), toml::Value::Float(2.0)),
+            ("pan_zoom".to_string(), toml::Value::Boolean(false)),
+        ]);
+        saver.init(20, 20, &options);
+
+        // Wait for the loader, a little at a time
+        let wait_for_image = |saver: &mut Slideshow, dt: f32| {
+            for _ in 0..500 {
+                saver.update(dt);
+                let taken = saver.take_images();
+                if !taken.is_empty() {
+                    return taken;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+            panic!("no image from the loader");
+        };
+
+        let first = wait_for_image(&mut saver, 0.0);
+        assert_eq!(saver.files.len(), 3);
+        assert_eq!(first.len(), 1);
+        // Fitted to the 20x20 output
+        assert_eq!(first[0].1.dimensions(), (20, 10));
+        let slot = first[0].0;
+        assert_eq!(saver.gpu[slot][3], 1.0);
+
+        // Nothing new until the interval is up, then the other slot
+        saver.update(1.0);
+        assert!(saver.take_images().is_empty());
+        let second = wait_for_image(&mut saver, 0.5);
+        assert_eq!(second[0].0, 1 - slot);
+        assert_ne!(second[0].1.get_pixel(0, 0), first[0].1.get_pixel(0, 0));
+
+        // A resize carries on with the same slides, sized for the new output
+        let shown = saver.shown;
+        saver.init(40, 40, &options);
+        assert_eq!(saver.shown, shown);
+        assert_eq!(saver.files.len(), 3);
+        saver.update(2.0);
+        let third = wait_for_image(&mut saver, 0.5);
+        assert_eq!(saver.shown, shown.map(|slide| slide + 1));
+        assert_eq!(third[0].1.dimensions(), (40, 20));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,272 @@
+//! WGSL validation
+//!
+//! Shaders are checked with naga, the same front end wgpu uses, before they
+//! are accepted: `COMMON_SHADER` and the generated parameter block are
+//! prepended exactly as the renderer does, then the module is parsed,
+//! validated and checked for the entry points and bindings the renderer
+//! provides (including the pass textures, see `passes`). Errors point into
+//! the fragment file (or the file it imports the offending line from), not
+//! the combined source.
+
+use super::header::{self, ShaderHeader};
+use super::{images, passes};
+use super::preprocess;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use std::fmt;
+
+/// Bindings the renderer always supplies: (group, binding)
+const PROVIDED_BINDINGS: &[(u32, u32)] = &[(0, 0), (0, 1)];
+
+/// Binding of a screensaver's first storage buffer in group 0
+pub const FIRST_STORAGE_BINDING: u32 = 2;
+
+/// A problem found in a shader, located in the fragment source if possible
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderError {
+    /// Imported file the location is in, if not the shader itself
+    pub file: Option<String>,
+    /// 1-based (line, column)
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl ShaderError {
+    pub(super) fn new(message: String) -> Self {
+        Self {
+            file: None,
+            location: None,
+            message,
+        }
+    }
+
+    /// `file:line:col: message`, for logs and `--list`
+    pub fn render(&self, file: &str) -> String {
+        let file = self.file.as_deref().unwrap_or(file);
+        match self.location {
+            Some((line, col)) => format!("{}:{}:{}: {}", file, line, col, self.message),
+            None => format!("{}: {}", file, self.message),
+        }
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.location) {
+            (Some(file), Some((line, col))) => write!(f, "{}:{}:{}: {}", file, line, col, self.message),
+            (_, Some((line, col))) => write!(f, "line {}:{}: {}", line, col, self.message),
+            _ => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Parse the header, expand imports and validate the complete shader built
+/// from `fragment`
+pub fn validate(fragment: &str) -> Result<ShaderHeader, ShaderError> {
+    validate_with_storage(fragment, 0)
+}
+
+/// Like `validate`, for a screensaver that also provides `storage_buffers`
+/// read-only storage buffers (see `Screensaver::storage`)
+pub fn validate_with_storage(fragment: &str, storage_buffers: usize) -> Result<ShaderHeader, ShaderError> {
+    let header = header::parse(fragment).map_err(ShaderError::new)?;
+    let expanded = preprocess::expand(fragment, super::custom_shader_dir().as_deref())?;
+    let prelude = super::prelude(&header);
+    let source = format!("{}{}", prelude, expanded.source);
+    let prelude_lines = prelude.matches('\n').count();
+
+    // Map a location in the combined source back to the file it came from
+    let located = |loc: Option<naga::SourceLocation>, message: String| {
+        let origin = loc.and_then(|loc| {
+            let line = (loc.line_number as usize).checked_sub(prelude_lines)?;
+            let (file, line) = expanded.origin(line)?;
+            Some((file.map(str::to_string), (line, loc.line_position as usize)))
+        });
+        let (file, location) = origin.map_or((None, None), |(file, loc)| (file, Some(loc)));
+        ShaderError {
+            file,
+            location,
+            message,
+        }
+    };
+
+    let module = naga::front::wgsl::parse_str(&source).map_err(|e| {
+        let mut message = e.message().to_string();
+        if let Some((_, label)) = e
+            .labels()
+            .find(|(_, label)| !label.is_empty() && !e.message().contains(label))
+        {
+            message = format!("{} ({})", message, label);
+        }
+        located(e.location(&source), message)
+    })?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::default())
+        .validate(&module)
+        .map_err(|e| located(e.location(&source), error_chain(&e)))?;
+
+    let entry_points = std::iter::once("fs_main".to_string())
+        .chain(header.passes.iter().map(|name| passes::entry_point(name)));
+    for entry_point in entry_points {
+        if !module
+            .entry_points
+            .iter()
+            .any(|ep| ep.name == entry_point && ep.stage == naga::ShaderStage::Fragment)
+        {
+            return Err(ShaderError::new(format!(
+                "missing required entry point `@fragment fn {}`",
+                entry_point
+            )));
+        }
+    }
+
+    let storage = FIRST_STORAGE_BINDING..FIRST_STORAGE_BINDING + storage_buffers as u32;
+    for (_, var) in module.global_variables.iter() {
+        let Some(ref binding) = var.binding else {
+            continue;
+        };
+        let name = var.name.as_deref().unwrap_or("?");
+        let is_storage = binding.group == 0 && storage.contains(&binding.binding);
+        // The sampler and one texture per pass
+        let is_pass = !header.passes.is_empty()
+            && binding.group == passes::PASS_GROUP
+            && binding.binding < passes::binding(header.passes.len());
+        // Then the image sampler and one texture per image
+        let is_image = !header.images.is_empty()
+            && binding.group == passes::PASS_GROUP
+            && (images::sampler_binding(header.passes.len())..images::binding(header.passes.len(), header.images.len()))
+                .contains(&binding.binding);
+        if !is_storage && !is_pass && !is_image && !PROVIDED_BINDINGS.contains(&(binding.group, binding.binding)) {
+            return Err(ShaderError::new(format!(
+                "`{}` uses @group({}) @binding({}), which the renderer doesn't provide",
+                name, binding.group, binding.binding
+            )));
+        }
+        if let naga::AddressSpace::Storage { access } = var.space
+            && access.contains(naga::StorageAccess::STORE)
+        {
+            return Err(ShaderError::new(format!(
+                "`{}` must be declared `var<storage, read>`",
+                name
+            )));
+        }
+    }
+
+    Ok(header)
+}
+
+/// An error and its causes on one line. The interesting part of a naga
+/// validation error is usually at the end of the chain.
+pub(super) fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut message = e.to_string();
+    let mut cause = e;
+    while let Some(next) = cause.source() {
+        message = format!("{}: {}", message, next);
+        cause = next;
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN: &str = "@fragment\nfn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {\n";
+
+    #[test]
+    fn builtin_shaders_validate() {
+        for saver in super::super::builtins() {
+            if let Err(e) = validate_with_storage(saver.fragment_shader(), saver.storage().len()) {
+                panic!("{}: {}", saver.name(), e.render(saver.name()));
+            }
+        }
+    }
+
+    #[test]
+    fn syntax_errors_point_into_the_fragment() {
+        let source = format!("// @param speed: f32 = 1.0\n{}    let x = 1.0\n    return vec4<f32>(x);\n}}\n", MAIN);
+        let err = validate(&source).unwrap_err();
+        assert_eq!(err.location.map(|(line, _)| line), Some(5), "{}", err);
+        assert!(err.render("rain.wgsl").starts_with("rain.wgsl:5:"), "{}", err);
+    }
+
+    #[test]
+    fn type_errors_and_unknown_names_are_caught() {
+        let source = format!("{}    return vec4<f32>(u.time, params.speed, 0.0, 1.0);\n}}\n", MAIN);
+        assert!(validate(&source).is_err());
+
+        let source = format!("{}    return u.time;\n}}\n", MAIN);
+        let err = validate(&source).unwrap_err();
+        assert!(err.location.is_some(), "{}", err);
+    }
+
+    #[test]
+    fn library_imports_validate_and_keep_line_numbers() {
+        let source = format!(
+            "#import hyprfresh/color\n#import hyprfresh/noise\n#import hyprfresh/palette\n#import hyprfresh/sdf\n{}    \
+             let c = hsv_to_rgb(rainbow(fbm(uv, 4) + simplex_noise(uv)));\n    return vec4<f32>(c, sd_circle(uv, 0.5));\n}}\n",
+            MAIN
+        );
+        validate(&source).unwrap();
+
+        let source = format!("#import hyprfresh/noise\n{}    return u.time;\n}}\n", MAIN);
+        let err = validate(&source).unwrap_err();
+        assert_eq!((err.file.as_deref(), err.location.map(|(line, _)| line)), (None, Some(4)), "{}", err);
+    }
+
+    #[test]
+    fn entry_point_and_bindings_are_checked() {
+        let err = validate("fn main() -> f32 { return 1.0; }\n").unwrap_err();
+        assert!(err.message.contains("fs_main"), "{}", err);
+
+        let source = format!(
+            "@group(1) @binding(0) var<uniform> extra: vec4<f32>;\n{}    return extra;\n}}\n",
+            MAIN
+        );
+        let err = validate(&source).unwrap_err();
+        assert!(err.message.contains("@group(1) @binding(0)"), "{}", err);
+
+        // Storage buffers only for screensavers that provide them, read-only
+        let source = format!(
+            "@group(0) @binding(2) var<storage, read> data: array<vec4<f32>>;\n{}    return data[0];\n}}\n",
+            MAIN
+        );
+        assert!(validate(&source).is_err());
+        validate_with_storage(&source, 1).unwrap();
+        let err = validate_with_storage(&source.replace("read>", "read_write>"), 1).unwrap_err();
+        assert!(err.message.contains("var<storage, read>"), "{}", err);
+    }
+
+    #[test]
+    fn passes_need_entry_points_and_get_textures() {
+        let source = format!(
+            "// @pass trail\n{}    return sample_pass(trail, uv);\n}}\n",
+            MAIN
+        );
+        let err = validate(&source).unwrap_err();
+        assert!(err.message.contains("fn fs_trail"), "{}", err);
+
+        let source = format!(
+            "{}\n@fragment\nfn fs_trail(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {{\n    \
+             return textureLoad(trail, vec2<i32>(pos.xy), 0) * 0.9;\n}}\n",
+            source
+        );
+        validate(&source).unwrap();
+    }
+
+    #[test]
+    fn images_are_bound_after_passes() {
+        let source = format!(
+            "// @image logo\n// @pass trail\n{}    return textureSample(logo, image_sampler, uv) + sample_pass(trail, uv);\n}}\n\
+             @fragment\nfn fs_trail() -> @location(0) vec4<f32> {{\n    return vec4<f32>(0.0);\n}}\n",
+            MAIN
+        );
+        validate(&source).unwrap();
+
+        let source = format!(
+            "// @image logo\n@group(1) @binding(3) var other: texture_2d<f32>;\n{}    return textureSample(other, image_sampler, uv);\n}}\n",
+            MAIN
+        );
+        let err = validate(&source).unwrap_err();
+        assert!(err.message.contains("@group(1) @binding(3)"), "{}", err);
+    }
+}
@@ -0,0 +1,115 @@
+//! Shader hot-reload for `--preview`
+//!
+//! Watches the directories holding the previewed shader's files with inotify
+//! (like the config watcher, so editors that save by renaming are caught) and,
+//! once a burst of changes settles, asks the renderer to rebuild its
+//! pipelines. Runs on the calloop event loop, next to the renderer.
+
+use crate::renderer::WaylandState;
+use crate::screensavers;
+use log::{debug, info, warn};
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use smithay_client_toolkit::reexports::calloop::{
+    generic::Generic,
+    timer::{TimeoutAction, Timer},
+    Interest, LoopHandle, Mode, PostAction,
+};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Editors often write a file in several steps; rebuild once they're done
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Files and directories currently watched
+#[derive(Default)]
+struct Watched {
+    names: HashSet<OsString>,
+    dirs: HashSet<PathBuf>,
+}
+
+impl Watched {
+    /// Watch `files`, forgetting the names watched before. Directories stay
+    /// watched; events for names not in the set are ignored anyway.
+    fn update(&mut self, inotify: &Inotify, files: &[PathBuf]) -> nix::Result<()> {
+        let before = std::mem::take(&mut self.names);
+        for file in files {
+            let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+                continue;
+            };
+            if !self.dirs.contains(dir) {
+                inotify.add_watch(
+                    dir,
+                    AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO | AddWatchFlags::IN_CREATE,
+                )?;
+                self.dirs.insert(dir.to_path_buf());
+            }
+            if self.names.insert(name.to_os_string()) && !before.contains(name) {
+                info!("Watching {} for changes", file.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild the renderer's shaders whenever one of the files of `saver`
+/// changes. The files are listed again after every rebuild, so imports added
+/// while editing are picked up.
+pub fn watch(handle: &LoopHandle<'static, WaylandState>, saver: &str) -> std::io::Result<()> {
+    let files = screensavers::shader_files(saver);
+    if files.is_empty() {
+        return Ok(());
+    }
+    let inotify = Rc::new(Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?);
+    let watched = Rc::new(RefCell::new(Watched::default()));
+    watched.borrow_mut().update(&inotify, &files)?;
+
+    let saver = saver.to_string();
+    let pending = Rc::new(Cell::new(false));
+    let timer_handle = handle.clone();
+    let timer_inotify = inotify.clone();
+    handle
+        .insert_source(
+            Generic::new(inotify, Interest::READ, Mode::Level),
+            move |_, inotify, _| {
+                let events = match inotify.as_ref().read_events() {
+                    Ok(events) => events,
+                    Err(nix::errno::Errno::EAGAIN) => return Ok(PostAction::Continue),
+                    Err(e) => return Err(e.into()),
+                };
+                let changed = {
+                    let watched = watched.borrow();
+                    events
+                        .iter()
+                        .any(|ev| ev.name.as_ref().is_some_and(|n| watched.names.contains(n)))
+                };
+                if !changed || pending.replace(true) {
+                    return Ok(PostAction::Continue);
+                }
+
+                let pending = pending.clone();
+                let watched = watched.clone();
+                let inotify = timer_inotify.clone();
+                let saver = saver.clone();
+                let timer = timer_handle.insert_source(Timer::from_duration(DEBOUNCE), move |_, _, state| {
+                    pending.set(false);
+                    debug!("Shader file changed, reloading");
+                    state.reload_shaders();
+                    let files = screensavers::shader_files(&saver);
+                    if let Err(e) = watched.borrow_mut().update(&inotify, &files) {
+                        warn!("Failed to watch the shader's files: {}", e);
+                    }
+                    TimeoutAction::Drop
+                });
+                if let Err(e) = timer {
+                    return Err(std::io::Error::other(e.to_string()));
+                }
+                Ok(PostAction::Continue)
+            },
+        )
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(())
+}
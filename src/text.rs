@@ -0,0 +1,336 @@
+//! Text rendering
+//!
+//! Glyphs are rasterized on the CPU with ab_glyph into a glyph atlas, a
+//! single-channel coverage image that the renderer uploads as a texture,
+//! and text is laid out as one quad per glyph over it. Without a configured
+//! font, or if it can't be read, the embedded DejaVu Sans Mono is used, so
+//! nothing depends on fontconfig or the fonts installed.
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont};
+use log::warn;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Font used when none is configured (see assets/fonts/LICENSE-DejaVu.txt)
+const FALLBACK_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono.ttf");
+
+/// Sides of the atlas, which starts small and doubles when it fills up
+const MIN_ATLAS_SIZE: u32 = 256;
+const MAX_ATLAS_SIZE: u32 = 4096;
+
+/// Empty pixels around each glyph, so sampling never picks up a neighbour
+const PADDING: u32 = 1;
+
+/// The embedded font
+pub fn fallback_font() -> FontArc {
+    FontArc::try_from_slice(FALLBACK_FONT).expect("embedded font is valid")
+}
+
+/// Load a TrueType or OpenType font file; `~` is expanded
+pub fn load_font(path: &str) -> Result<FontArc, String> {
+    let path = match path.strip_prefix("~/").zip(std::env::var("HOME").ok()) {
+        Some((rest, home)) => PathBuf::from(home).join(rest),
+        None => PathBuf::from(path),
+    };
+    let data = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    FontArc::try_from_vec(data).map_err(|_| format!("{} isn't a TrueType or OpenType font", path.display()))
+}
+
+/// The font at `path`, or the embedded one if there's none or it can't be
+/// loaded
+pub fn font_or_fallback(path: Option<&str>) -> FontArc {
+    match path.map(load_font) {
+        Some(Ok(font)) => font,
+        Some(Err(e)) => {
+            warn!("{}, using the built-in font", e);
+            fallback_font()
+        }
+        None => fallback_font(),
+    }
+}
+
+/// How lines of different widths line up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// Where a glyph is in the atlas
+#[derive(Debug, Clone, Copy)]
+struct AtlasGlyph {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// Top left corner relative to the pen position on the baseline
+    offset: [f32; 2],
+}
+
+/// One glyph of laid-out text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphQuad {
+    /// Left, top, right, bottom in pixels from the top left of the text
+    pub rect: [f32; 4],
+    /// The same corners in the atlas, from 0 to 1
+    pub uv: [f32; 4],
+}
+
+/// Text ready to draw
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    pub quads: Vec<GlyphQuad>,
+    /// Width and height of the whole block in pixels
+    pub size: [f32; 2],
+}
+
+/// Glyphs of one font at one size, packed into rows of a square coverage
+/// image as text needs them
+pub struct GlyphAtlas {
+    font: FontArc,
+    scale: PxScale,
+    size: u32,
+    pixels: Vec<u8>,
+    /// None for glyphs with nothing to draw (spaces)
+    glyphs: HashMap<GlyphId, Option<AtlasGlyph>>,
+    /// Where the next glyph goes, and the height of the row it's in
+    cursor: (u32, u32),
+    row_height: u32,
+    /// Whether `pixels` changed since the renderer last uploaded them
+    dirty: bool,
+}
+
+impl GlyphAtlas {
+    /// An empty atlas for text `px` pixels high
+    pub fn new(font: FontArc, px: f32) -> Self {
+        // Room for a few dozen glyphs before it has to grow
+        let size = ((px * 6.0) as u32).next_power_of_two().clamp(MIN_ATLAS_SIZE, MAX_ATLAS_SIZE);
+        Self {
+            font,
+            scale: PxScale::from(px),
+            size,
+            pixels: vec![0; (size * size) as usize],
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+            dirty: true,
+        }
+    }
+
+    /// Width and height of the atlas image
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Coverage, one byte per pixel, row by row
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Whether the pixels changed since the last call
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Drop every glyph, resizing to `size`
+    fn clear(&mut self, size: u32) {
+        self.size = size;
+        self.pixels = vec![0; (size * size) as usize];
+        self.glyphs.clear();
+        self.cursor = (0, 0);
+        self.row_height = 0;
+        self.dirty = true;
+    }
+
+    /// Rasterize a glyph into the atlas unless it's there already. Err if
+    /// it doesn't fit.
+    fn insert(&mut self, id: GlyphId) -> Result<Option<AtlasGlyph>, ()> {
+        if let Some(glyph) = self.glyphs.get(&id) {
+            return Ok(*glyph);
+        }
+        let Some(outline) = self
+            .font
+            .outline_glyph(id.with_scale_and_position(self.scale, ab_glyph::point(0.0, 0.0)))
+        else {
+            self.glyphs.insert(id, None);
+            return Ok(None);
+        };
+        let bounds = outline.px_bounds();
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+
+        if self.cursor.0 + width + PADDING > self.size {
+            self.cursor = (0, self.cursor.1 + self.row_height);
+            self.row_height = 0;
+        }
+        if self.cursor.0 + width + PADDING > self.size || self.cursor.1 + height + PADDING > self.size {
+            return Err(());
+        }
+        let (x, y) = (self.cursor.0 + PADDING, self.cursor.1 + PADDING);
+        let size = self.size;
+        let pixels = &mut self.pixels;
+        outline.draw(|gx, gy, coverage| {
+            if gx < width && gy < height {
+                let i = ((y + gy) * size + x + gx) as usize;
+                pixels[i] = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        });
+        self.cursor.0 += width + PADDING;
+        self.row_height = self.row_height.max(height + PADDING);
+        self.dirty = true;
+
+        let glyph = AtlasGlyph {
+            x,
+            y,
+            width,
+            height,
+            offset: [bounds.min.x, bounds.min.y],
+        };
+        self.glyphs.insert(id, Some(glyph));
+        Ok(Some(glyph))
+    }
+
+    /// Make room for all of `ids`: start over if the atlas is full of
+    /// other glyphs, then grow. Glyphs that don't fit even then are left out.
+    fn insert_all(&mut self, ids: &[GlyphId]) {
+        let mut cleared = false;
+        while ids.iter().any(|&id| self.insert(id).is_err()) {
+            let size = match (cleared, self.size < MAX_ATLAS_SIZE) {
+                (false, _) => self.size,
+                (true, true) => self.size * 2,
+                (true, false) => {
+                    warn!("Text is too large for the glyph atlas; some glyphs are missing");
+                    return;
+                }
+            };
+            cleared = true;
+            self.clear(size);
+        }
+    }
+
+    /// Lay out `text` (lines separated by `\n`), adding its glyphs to the
+    /// atlas. Positions are whole pixels, so glyphs stay sharp.
+    pub fn layout(&mut self, text: &str, align: Align) -> TextLayout {
+        let scaled = self.font.as_scaled(self.scale);
+        let lines: Vec<Vec<GlyphId>> = text
+            .split('\n')
+            .map(|line| line.chars().map(|c| scaled.glyph_id(c)).collect())
+            .collect();
+        let all: Vec<GlyphId> = lines.iter().flatten().copied().collect();
+        self.insert_all(&all);
+
+        // Pen position of every glyph along its line, and each line's width
+        let scaled = self.font.as_scaled(self.scale);
+        let advances: Vec<(Vec<f32>, f32)> = lines
+            .iter()
+            .map(|line| {
+                let mut pen = 0.0;
+                let mut positions = Vec::with_capacity(line.len());
+                for (i, &id) in line.iter().enumerate() {
+                    if i > 0 {
+                        pen += scaled.kern(line[i - 1], id);
+                    }
+                    positions.push(pen);
+                    pen += scaled.h_advance(id);
+                }
+                (positions, pen)
+            })
+            .collect();
+
+        let width = advances.iter().map(|(_, w)| *w).fold(0.0, f32::max).ceil();
+        let line_height = (scaled.height() + scaled.line_gap()).ceil();
+        let height = line_height * (lines.len() - 1) as f32 + scaled.height().ceil();
+
+        let atlas = self.size as f32;
+        let mut quads = Vec::new();
+        for (row, (line, (positions, line_width))) in lines.iter().zip(&advances).enumerate() {
+            let start = match align {
+                Align::Left => 0.0,
+                Align::Center => ((width - line_width) / 2.0).round(),
+                Align::Right => (width - line_width).round(),
+            };
+            let baseline = (scaled.ascent() + row as f32 * line_height).round();
+            for (&id, pen) in line.iter().zip(positions) {
+                let Some(Some(glyph)) = self.glyphs.get(&id) else {
+                    continue;
+                };
+                let left = start + pen.round() + glyph.offset[0];
+                let top = baseline + glyph.offset[1];
+                quads.push(GlyphQuad {
+                    rect: [left, top, left + glyph.width as f32, top + glyph.height as f32],
+                    uv: [
+                        glyph.x as f32 / atlas,
+                        glyph.y as f32 / atlas,
+                        (glyph.x + glyph.width) as f32 / atlas,
+                        (glyph.y + glyph.height) as f32 / atlas,
+                    ],
+                });
+            }
+        }
+        TextLayout {
+            quads,
+            size: [width, height],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lays_out_lines_on_whole_pixels() {
+        let mut atlas = GlyphAtlas::new(fallback_font(), 32.0);
+        let one = atlas.layout("12:34", Align::Left);
+        assert_eq!(one.quads.len(), 5);
+        let two = atlas.layout("12:34\n5", Align::Left);
+        assert_eq!(two.quads.len(), 6);
+        assert_eq!(two.size[0], one.size[0]);
+        assert!(two.size[1] > one.size[1] * 1.5);
+        for quad in &two.quads {
+            assert!(quad.rect.iter().all(|v| v.fract() == 0.0), "{:?}", quad);
+            assert!(quad.uv.iter().all(|v| (0.0..=1.0).contains(v)), "{:?}", quad);
+        }
+        // Monospaced, so every glyph starts further right than the last
+        assert!(one.quads.windows(2).all(|w| w[1].rect[0] > w[0].rect[0]));
+        // Spaces take room but have nothing to draw
+        let spaced = atlas.layout("1 2", Align::Left);
+        assert_eq!(spaced.quads.len(), 2);
+    }
+
+    #[test]
+    fn aligns_shorter_lines() {
+        let mut atlas = GlyphAtlas::new(fallback_font(), 32.0);
+        let left = atlas.layout("1111\n1", Align::Left);
+        let right = atlas.layout("1111\n1", Align::Right);
+        let center = atlas.layout("1111\n1", Align::Center);
+        let last = |layout: &TextLayout| layout.quads[4].rect[0];
+        assert!(last(&left) < last(&center) && last(&center) < last(&right));
+        assert!((right.quads[4].rect[2] - right.quads[3].rect[2]).abs() <= 1.0);
+    }
+
+    #[test]
+    fn atlas_grows_when_full() {
+        let mut atlas = GlyphAtlas::new(fallback_font(), 40.0);
+        assert!(atlas.take_dirty());
+        let start = atlas.size();
+        // Latin, Greek and Cyrillic
+        let text: String = ('!'..='~').chain('Α'..='Ω').chain('А'..='я').collect();
+        let layout = atlas.layout(&text, Align::Left);
+        assert_eq!(layout.quads.len(), text.chars().count());
+        assert!(atlas.size() > start);
+        assert!(atlas.take_dirty());
+        assert!(atlas.pixels().contains(&255));
+
+        // Cached glyphs don't touch the pixels again
+        atlas.layout("ABC", Align::Left);
+        assert!(!atlas.take_dirty());
+    }
+
+    #[test]
+    fn missing_fonts_fall_back() {
+        assert!(load_font("/nonexistent/font.ttf").is_err());
+        let layout = GlyphAtlas::new(font_or_fallback(Some("/nonexistent/font.ttf")), 16.0).layout("a", Align::Left);
+        assert_eq!(layout.quads.len(), 1);
+    }
+}